@@ -2,18 +2,111 @@
 #![deny(clippy::pedantic)]
 #![allow(clippy::result_large_err)]
 
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::{Parser, Subcommand, ValueEnum};
+use llvmup::{
+    Llvmup,
+    PkgConfigGranularity,
+    Toolchain,
+    ToolchainConfig,
+    ToolchainGraphFormat,
+    ToolchainHandle,
+    ToolchainInstallOptions,
+    ToolchainLockfile,
+    ToolchainPlatform,
+    TOOLCHAIN_LOCKFILE_NAME,
+};
 use snafu::prelude::*;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
 
 #[cfg(feature = "tracing")]
 use tracing_subscriber::prelude::*;
 
 #[derive(Debug, Snafu)]
 pub enum Error {
+    Llvmup {
+        source: llvmup::Error,
+    },
+    LlvmupConfigNotFound,
+    LlvmupLockfileNotFound,
+    StdEnvJoinPaths {
+        source: std::env::JoinPathsError,
+    },
+    TokioFsTryExists {
+        source: std::io::Error,
+    },
+    TokioFsWrite {
+        source: std::io::Error,
+    },
     TracingSubscriberTryInit {
         source: tracing_subscriber::util::TryInitError,
     },
 }
 
+#[derive(Parser)]
+#[command(name = "llvmup", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Answer `llvm-config` queries (e.g., `--libs --system-libs core x86`) for the project's toolchain.
+    ///
+    /// When invoked through a link named `llvm-config`, `llvmup` behaves as if run with this command.
+    #[command(disable_help_flag = true, disable_version_flag = true)]
+    Config {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Print the environment (i.e., `PKG_CONFIG_PATH` and `CMAKE_TOOLCHAIN_FILE`) for building against the project's
+    /// toolchain, after generating its pkg-config and `CMake` toolchain files.
+    Env {
+        /// Generate a pkg-config package per component (e.g., `llvm`) instead of per library target.
+        #[arg(long)]
+        per_component: bool,
+    },
+    /// Export the dependency graph of the project's toolchain.
+    Graph {
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+        /// Write the graph to a file instead of stdout.
+        #[arg(long, short)]
+        output: Option<Utf8PathBuf>,
+        /// Only export the part of the graph reachable from these targets.
+        roots: Vec<String>,
+    },
+    /// Install the toolchains locked in the project's `llvmup.lock`, or else those described by its `llvmup.toml`.
+    Install {
+        /// Fail if `llvmup.lock` is missing or resolution would change it.
+        #[arg(long)]
+        locked: bool,
+    },
+    /// Resolve the project's `llvmup.toml` against the published releases and refresh `llvmup.lock`.
+    Update,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GraphFormat {
+    Dot,
+    Json,
+    Mermaid,
+}
+
+impl From<GraphFormat> for ToolchainGraphFormat {
+    fn from(format: GraphFormat) -> Self {
+        match format {
+            GraphFormat::Dot => Self::Dot,
+            GraphFormat::Json => Self::Json,
+            GraphFormat::Mermaid => Self::Mermaid,
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), self::Error> {
     #[cfg(feature = "tracing")]
@@ -23,5 +116,249 @@ async fn main() -> Result<(), self::Error> {
         .try_init()
         .context(TracingSubscriberTryInitSnafu)?;
 
+    let mut args = std::env::args_os().collect::<Vec<_>>();
+    // NOTE: Build scripts expecting `llvm-config` (e.g., through `LLVM_CONFIG_PATH`) can be pointed at a link to
+    // `llvmup` named after it.
+    if args
+        .first()
+        .and_then(|arg| Utf8Path::new(arg.to_str()?).file_stem())
+        .is_some_and(|stem| stem.starts_with("llvm-config"))
+    {
+        args.insert(1, "config".into());
+    }
+    let cli = Cli::parse_from(args);
+
+    match cli.command {
+        Command::Config { args } => config(&args).await,
+        Command::Env { per_component } => env(per_component).await,
+        Command::Graph { format, output, roots } => graph(format, output.as_deref(), &roots).await,
+        Command::Install { locked } => install(locked).await,
+        Command::Update => update().await,
+    }
+}
+
+async fn install(locked: bool) -> Result<(), self::Error> {
+    let mut llvmup = Llvmup::builder().build().context(LlvmupSnafu)?;
+    let (lock_path, config) = discover_config().await?;
+    let platforms = [ToolchainPlatform::detect()];
+
+    let lockfile = load_lockfile(&lock_path).await?;
+    ensure!(!locked || lockfile.is_some(), LlvmupLockfileNotFoundSnafu);
+    // NOTE: Locked toolchains are installed as recorded (so long as `llvmup.toml` still allows them); otherwise the
+    // configuration is resolved against the published releases and locked anew.
+    let locked_toolchains = match lockfile.as_ref().map(|lockfile| lockfile.config_toolchains(&config, &platforms)) {
+        Some(Ok(toolchains)) => Some(toolchains),
+        Some(Err(source)) if locked || !source.is_outdated() => {
+            return Err(llvmup::Error::from(source)).context(LlvmupSnafu);
+        },
+        Some(Err(_)) | None => None,
+    };
+    let relock = locked_toolchains.is_none();
+
+    let toolchains = match locked_toolchains {
+        Some(toolchains) => toolchains,
+        None => llvmup
+            .config_toolchains_from_releases(&config, &platforms)
+            .await
+            .context(LlvmupSnafu)?,
+    };
+
+    let contexts = toolchains.iter().map(|toolchain| toolchain.context).collect::<Vec<_>>();
+    let handles = register_toolchains(&mut llvmup, toolchains);
+
+    // NOTE: Re-locking only replaces the host's entry; the locks of other platforms are kept.
+    let lockfile = match lockfile {
+        Some(lockfile) if !relock => lockfile,
+        lockfile => lock_toolchains(&llvmup, &handles, lockfile, &lock_path).await?,
+    };
+
+    for (context, handle) in contexts.into_iter().zip(handles) {
+        let lock = lockfile.toolchain(&context).context(LlvmupLockfileNotFoundSnafu)?;
+        llvmup
+            .install_toolchain_locked(handle, ToolchainInstallOptions::default(), lock)
+            .await
+            .context(LlvmupSnafu)?;
+    }
+
     Ok(())
 }
+
+async fn env(per_component: bool) -> Result<(), self::Error> {
+    let mut llvmup = Llvmup::builder().build().context(LlvmupSnafu)?;
+    let (_, toolchains) = resolve_config_toolchains(&llvmup).await?;
+    let toolchain = toolchains.into_iter().next().context(LlvmupConfigNotFoundSnafu)?;
+    let context = toolchain.context;
+    let components = toolchain.components.clone();
+    let handle = llvmup.register_toolchain(toolchain);
+    let analysis = llvmup
+        .cached_analysis(handle, &components)
+        .await
+        .context(LlvmupSnafu)?;
+    let generator = llvmup
+        .generator(context, analysis.get(), BTreeSet::new(), BTreeMap::new())
+        .context(LlvmupSnafu)?;
+    let granularity = if per_component {
+        PkgConfigGranularity::Component
+    } else {
+        PkgConfigGranularity::Target
+    };
+    let pkg_config = generator
+        .generate_pkg_config(granularity)
+        .map_err(llvmup::Error::from)
+        .context(LlvmupSnafu)?;
+    let pkg_config_dir = pkg_config.pkg_config_dir();
+    pkg_config
+        .emit(&pkg_config_dir)
+        .await
+        .map_err(llvmup::Error::from)
+        .context(LlvmupSnafu)?;
+
+    let mut paths = vec![PathBuf::from(&pkg_config_dir)];
+    if let Some(path) = std::env::var_os("PKG_CONFIG_PATH") {
+        paths.extend(std::env::split_paths(&path).filter(|path| path != pkg_config_dir.as_std_path()));
+    }
+    let path = std::env::join_paths(paths).context(StdEnvJoinPathsSnafu)?;
+    let path = path.to_string_lossy().replace('\'', "'\\''");
+    println!("export PKG_CONFIG_PATH='{path}'");
+
+    // NOTE: `CMake` (3.21 and later) reads the toolchain file from the environment when none is given.
+    let cmake_toolchain_file = llvmup.cmake_toolchain_file(handle).context(LlvmupSnafu)?;
+    let cmake_toolchain_file_path = cmake_toolchain_file.path();
+    cmake_toolchain_file
+        .emit(&cmake_toolchain_file_path)
+        .await
+        .map_err(llvmup::Error::from)
+        .context(LlvmupSnafu)?;
+    let path = cmake_toolchain_file_path.as_str().replace('\'', "'\\''");
+    println!("export CMAKE_TOOLCHAIN_FILE='{path}'");
+    Ok(())
+}
+
+async fn graph(format: GraphFormat, output: Option<&Utf8Path>, roots: &[String]) -> Result<(), self::Error> {
+    let mut llvmup = Llvmup::builder().build().context(LlvmupSnafu)?;
+    let (_, toolchains) = resolve_config_toolchains(&llvmup).await?;
+    let toolchain = toolchains.into_iter().next().context(LlvmupConfigNotFoundSnafu)?;
+    let components = toolchain.components.clone();
+    let handle = llvmup.register_toolchain(toolchain);
+    let analysis = llvmup
+        .cached_analysis(handle, &components)
+        .await
+        .context(LlvmupSnafu)?;
+    let roots = roots.iter().map(String::as_str).collect::<Vec<_>>();
+    let graph = analysis
+        .get()
+        .export_graph(format.into(), &roots)
+        .map_err(llvmup::Error::from)
+        .context(LlvmupSnafu)?;
+    if let Some(output) = output {
+        tokio::fs::write(output, graph).await.context(TokioFsWriteSnafu)?;
+    } else {
+        print!("{graph}");
+    }
+    Ok(())
+}
+
+async fn config(args: &[String]) -> Result<(), self::Error> {
+    let mut llvmup = Llvmup::builder().build().context(LlvmupSnafu)?;
+    let (_, toolchains) = resolve_config_toolchains(&llvmup).await?;
+    let toolchain = toolchains.into_iter().next().context(LlvmupConfigNotFoundSnafu)?;
+    let components = toolchain.components.clone();
+    let handle = llvmup.register_toolchain(toolchain);
+    let analysis = llvmup
+        .cached_analysis(handle, &components)
+        .await
+        .context(LlvmupSnafu)?;
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let output = llvmup
+        .llvm_config(analysis.get())
+        .and_then(|config| config.query(&args).map_err(llvmup::Error::from))
+        .context(LlvmupSnafu)?;
+    print!("{output}");
+    Ok(())
+}
+
+async fn update() -> Result<(), self::Error> {
+    let mut llvmup = Llvmup::builder().build().context(LlvmupSnafu)?;
+    let (lock_path, config) = discover_config().await?;
+    // NOTE: Every platform configured in `llvmup.toml` is locked, not only the host, so that the lockfile can be
+    // shared across machines.
+    let platforms = config.platforms(ToolchainPlatform::detect()).into_iter().collect::<Vec<_>>();
+    let toolchains = llvmup
+        .config_toolchains_from_releases(&config, &platforms)
+        .await
+        .context(LlvmupSnafu)?;
+    let handles = register_toolchains(&mut llvmup, toolchains);
+    let lockfile = load_lockfile(&lock_path).await?;
+    lock_toolchains(&llvmup, &handles, lockfile, &lock_path).await?;
+    Ok(())
+}
+
+/// Find the project's `llvmup.toml`, along with the path of its `llvmup.lock`.
+async fn discover_config() -> Result<(Utf8PathBuf, ToolchainConfig), self::Error> {
+    let (path, config) = ToolchainConfig::discover()
+        .await
+        .map_err(llvmup::Error::from)
+        .context(LlvmupSnafu)?
+        .context(LlvmupConfigNotFoundSnafu)?;
+    Ok((path.with_file_name(TOOLCHAIN_LOCKFILE_NAME), config))
+}
+
+/// Resolve the project's toolchains: the locked ones if `llvmup.lock` still matches `llvmup.toml`, and otherwise the
+/// greatest installed releases satisfying it.
+async fn resolve_config_toolchains(llvmup: &Llvmup) -> Result<(Utf8PathBuf, Vec<Toolchain>), self::Error> {
+    let (lock_path, config) = discover_config().await?;
+    let platforms = [ToolchainPlatform::detect()];
+    if let Some(lockfile) = load_lockfile(&lock_path).await? {
+        match lockfile.config_toolchains(&config, &platforms) {
+            Ok(toolchains) => return Ok((lock_path, toolchains)),
+            Err(source) if !source.is_outdated() => return Err(llvmup::Error::from(source)).context(LlvmupSnafu),
+            Err(_) => {},
+        }
+    }
+    let toolchains = llvmup
+        .config_toolchains(&config, &platforms)
+        .await
+        .context(LlvmupSnafu)?;
+    Ok((lock_path, toolchains))
+}
+
+fn register_toolchains(llvmup: &mut Llvmup, toolchains: Vec<Toolchain>) -> Vec<ToolchainHandle> {
+    toolchains
+        .into_iter()
+        .map(|toolchain| llvmup.register_toolchain(toolchain))
+        .collect()
+}
+
+async fn load_lockfile(lock_path: &Utf8Path) -> Result<Option<ToolchainLockfile>, self::Error> {
+    if !tokio::fs::try_exists(lock_path).await.context(TokioFsTryExistsSnafu)? {
+        return Ok(None);
+    }
+    let lockfile = ToolchainLockfile::load(lock_path)
+        .await
+        .map_err(llvmup::Error::from)
+        .context(LlvmupSnafu)?;
+    Ok(Some(lockfile))
+}
+
+/// Lock the toolchains of `handles` into `lockfile` (if any) and store the result at `lock_path`.
+async fn lock_toolchains(
+    llvmup: &Llvmup,
+    handles: &[ToolchainHandle],
+    lockfile: Option<ToolchainLockfile>,
+    lock_path: &Utf8Path,
+) -> Result<ToolchainLockfile, self::Error> {
+    let locked = llvmup.lock_toolchains(handles).await.context(LlvmupSnafu)?;
+    let lockfile = match lockfile {
+        Some(mut lockfile) => {
+            lockfile.update(locked);
+            lockfile
+        },
+        None => locked,
+    };
+    lockfile
+        .store(lock_path)
+        .await
+        .map_err(llvmup::Error::from)
+        .context(LlvmupSnafu)?;
+    Ok(lockfile)
+}
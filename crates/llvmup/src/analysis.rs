@@ -1,8 +1,13 @@
 use camino::Utf8Path;
 use indexmap::IndexMap;
 use petgraph::graphmap::GraphMap;
+use serde::{Deserialize, Serialize};
 use snafu::prelude::*;
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+use yoke::{erased::ErasedArcCart, Yoke, Yokeable};
 use zerovec::VarZeroSlice;
 
 #[cfg(feature = "ahash")]
@@ -11,13 +16,23 @@ use ahash::AHashMap;
 use std::collections::HashMap;
 
 use crate::{
-    toolchain::{component::manifest::ManifestCMakeInherentTarget, ToolchainHandle},
+    analysis::genex::GenexContext,
+    toolchain::{component::manifest::ManifestCMakeInherentTarget, platform::ToolchainSys, ToolchainHandle},
     ManifestCMakeImportedTarget,
+    OwnedToolchainComponentManifests,
     ToolchainComponent,
     ToolchainComponentManifest,
+    ToolchainPlatform,
 };
 
+pub mod backends;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod closure;
 pub mod dependencies;
+pub mod export;
+pub mod genex;
+pub mod llvm_config;
 
 pub type ToolchainComponentsTargets<'a> =
     IndexMap<ToolchainComponent, IndexMap<&'a str, &'a ManifestCMakeInherentTarget<'a>>>;
@@ -35,14 +50,24 @@ pub struct ToolchainComponentDependencyNode<'a> {
     pub component: Option<ToolchainComponent>,
     pub name: &'a str,
     pub kind: ToolchainComponentDependencyNodeKind,
+    pub interface_compile_definitions: &'a VarZeroSlice<str>,
+    pub interface_compile_features: &'a VarZeroSlice<str>,
+    pub interface_compile_options: &'a VarZeroSlice<str>,
     pub interface_include_directories: &'a VarZeroSlice<str>,
     pub interface_link_directories: &'a VarZeroSlice<str>,
     pub interface_link_libraries: &'a VarZeroSlice<str>,
+    pub interface_link_options: &'a VarZeroSlice<str>,
+    pub interface_system_include_directories: &'a VarZeroSlice<str>,
     pub location: Option<&'a str>,
+    /// The import library of a shared library, which is what is linked against on Windows.
+    pub implib: Option<&'a str>,
+    /// The object files an object library contributes to its dependents.
+    pub objects: &'a VarZeroSlice<str>,
 }
 
 #[cfg_attr(feature = "debug", derive(Debug))]
-#[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub enum ToolchainComponentDependencyNodeKind {
     Executable,
     Interface,
@@ -65,110 +90,242 @@ impl core::hash::Hash for ToolchainComponentDependencyNode<'_> {
     }
 }
 
-impl<'a> From<&'a str> for ToolchainComponentDependencyNode<'a> {
-    fn from(name: &'a str) -> Self {
-        fn kind_heuristic(name: &str) -> (&str, ToolchainComponentDependencyNodeKind) {
-            if let Some(name) = name.strip_prefix("-framework ") {
-                (name, ToolchainComponentDependencyNodeKind::Shared { framework: true })
-            } else if let Some(name) = name.strip_prefix("lib").unwrap_or(name).strip_suffix(".a") {
-                (name, ToolchainComponentDependencyNodeKind::Static { framework: false })
-            } else if let Some(name) = name.strip_prefix("lib").unwrap_or(name).strip_suffix(".so") {
-                (name, ToolchainComponentDependencyNodeKind::Shared { framework: false })
-            } else {
-                #[allow(clippy::if_same_then_else)]
-                (name, ToolchainComponentDependencyNodeKind::Shared { framework: false })
+impl<'a> ToolchainComponentDependencyNode<'a> {
+    /// A node for a library not provided by any component (e.g., `m`, `-framework Foundation`, `ws2_32.lib`), whose
+    /// kind is guessed from the naming conventions of the target `platform`.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn external(name: &'a str, platform: ToolchainPlatform) -> Self {
+        fn kind_heuristic(name: &str, sys: ToolchainSys) -> (&str, ToolchainComponentDependencyNodeKind) {
+            match sys {
+                ToolchainSys::Macos if name.starts_with("-framework ") => {
+                    let name = name.trim_start_matches("-framework ");
+                    (name, ToolchainComponentDependencyNodeKind::Shared { framework: true })
+                },
+                ToolchainSys::Windows => {
+                    // NOTE: On Windows, `.lib` files are usually import libraries for system DLLs.
+                    let name = name.strip_suffix(".lib").unwrap_or(name);
+                    (name, ToolchainComponentDependencyNodeKind::Shared { framework: false })
+                },
+                _ => {
+                    let unprefixed = name.strip_prefix("lib").unwrap_or(name);
+                    if let Some(name) = unprefixed.strip_suffix(".a") {
+                        (name, ToolchainComponentDependencyNodeKind::Static { framework: false })
+                    } else if let Some(name) = ["so", "dylib", "tbd"]
+                        .iter()
+                        .find_map(|ext| unprefixed.strip_suffix(ext)?.strip_suffix('.'))
+                    {
+                        (name, ToolchainComponentDependencyNodeKind::Shared { framework: false })
+                    } else {
+                        (name, ToolchainComponentDependencyNodeKind::Shared { framework: false })
+                    }
+                },
             }
         }
-        let component = None;
-        let (name, kind) = kind_heuristic(name);
-        let interface_include_directories = VarZeroSlice::new_empty();
-        let interface_link_directories = VarZeroSlice::new_empty();
-        let interface_link_libraries = VarZeroSlice::new_empty();
-        let location = None;
+        let (name, kind) = kind_heuristic(name, platform.sys());
+        Self::external_with_kind(name, kind)
+    }
+
+    /// A node for a library not provided by any component, whose kind is already known.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn external_with_kind(name: &'a str, kind: ToolchainComponentDependencyNodeKind) -> Self {
         Self {
-            component,
+            component: None,
             name,
             kind,
-            interface_include_directories,
-            interface_link_directories,
-            interface_link_libraries,
-            location,
+            interface_compile_definitions: VarZeroSlice::new_empty(),
+            interface_compile_features: VarZeroSlice::new_empty(),
+            interface_compile_options: VarZeroSlice::new_empty(),
+            interface_include_directories: VarZeroSlice::new_empty(),
+            interface_link_directories: VarZeroSlice::new_empty(),
+            interface_link_libraries: VarZeroSlice::new_empty(),
+            interface_link_options: VarZeroSlice::new_empty(),
+            interface_system_include_directories: VarZeroSlice::new_empty(),
+            location: None,
+            implib: None,
+            objects: VarZeroSlice::new_empty(),
         }
     }
-}
 
-impl<'a> ToolchainComponentDependencyNode<'a> {
     #[must_use]
+    #[allow(clippy::too_many_lines)]
     #[cfg_attr(feature = "tracing", tracing::instrument)]
-    pub fn new(component: ToolchainComponent, name: &'a str, target: &'a ManifestCMakeInherentTarget<'a>) -> Self {
-        match target {
+    pub fn new(
+        component: ToolchainComponent,
+        name: &'a str,
+        target: &'a ManifestCMakeInherentTarget<'a>,
+        config: &str,
+    ) -> Self {
+        let (ManifestCMakeInherentTarget::Executable {
+            interface_compile_definitions,
+            interface_compile_features,
+            interface_compile_options,
+            interface_include_directories,
+            interface_link_directories,
+            interface_link_libraries,
+            interface_link_options,
+            interface_system_include_directories,
+            ..
+        }
+        | ManifestCMakeInherentTarget::InterfaceLibrary {
+            interface_compile_definitions,
+            interface_compile_features,
+            interface_compile_options,
+            interface_include_directories,
+            interface_link_directories,
+            interface_link_libraries,
+            interface_link_options,
+            interface_system_include_directories,
+        }
+        | ManifestCMakeInherentTarget::ModuleLibrary {
+            interface_compile_definitions,
+            interface_compile_features,
+            interface_compile_options,
+            interface_include_directories,
+            interface_link_directories,
+            interface_link_libraries,
+            interface_link_options,
+            interface_system_include_directories,
+            ..
+        }
+        | ManifestCMakeInherentTarget::ObjectLibrary {
+            interface_compile_definitions,
+            interface_compile_features,
+            interface_compile_options,
+            interface_include_directories,
+            interface_link_directories,
+            interface_link_libraries,
+            interface_link_options,
+            interface_system_include_directories,
+            ..
+        }
+        | ManifestCMakeInherentTarget::SharedLibrary {
+            interface_compile_definitions,
+            interface_compile_features,
+            interface_compile_options,
+            interface_include_directories,
+            interface_link_directories,
+            interface_link_libraries,
+            interface_link_options,
+            interface_system_include_directories,
+            ..
+        }
+        | ManifestCMakeInherentTarget::StaticLibrary {
+            interface_compile_definitions,
+            interface_compile_features,
+            interface_compile_options,
+            interface_include_directories,
+            interface_link_directories,
+            interface_link_libraries,
+            interface_link_options,
+            interface_system_include_directories,
+            ..
+        }
+        | ManifestCMakeInherentTarget::UnknownLibrary {
+            interface_compile_definitions,
+            interface_compile_features,
+            interface_compile_options,
+            interface_include_directories,
+            interface_link_directories,
+            interface_link_libraries,
+            interface_link_options,
+            interface_system_include_directories,
+            ..
+        }) = target;
+        let mut objects = VarZeroSlice::new_empty();
+        let mut implib = None;
+        let (kind, location) = match target {
             ManifestCMakeInherentTarget::Executable {
-                interface_include_directories,
-                interface_link_directories,
-                interface_link_libraries,
+                imported_configurations,
+                location,
+                locations,
+                ..
+            } => (
+                ToolchainComponentDependencyNodeKind::Executable,
+                Some(locations.select(config, imported_configurations, location.as_ref())),
+            ),
+            ManifestCMakeInherentTarget::InterfaceLibrary { .. } => {
+                (ToolchainComponentDependencyNodeKind::Interface, None)
+            },
+            ManifestCMakeInherentTarget::ModuleLibrary {
+                imported_configurations,
                 location,
+                locations,
                 ..
-            } => Self {
-                component: Some(component),
-                name,
-                kind: ToolchainComponentDependencyNodeKind::Executable,
-                interface_include_directories: interface_include_directories.as_slice(),
-                interface_link_directories: interface_link_directories.as_slice(),
-                interface_link_libraries: interface_link_libraries.as_slice(),
-                location: Some(*location),
+            } => (
+                ToolchainComponentDependencyNodeKind::Module,
+                Some(locations.select(config, imported_configurations, location.as_ref())),
+            ),
+            ManifestCMakeInherentTarget::ObjectLibrary { imported_objects, .. } => {
+                objects = imported_objects.as_slice();
+                (ToolchainComponentDependencyNodeKind::Object, None)
             },
             ManifestCMakeInherentTarget::SharedLibrary {
                 framework,
-                interface_include_directories,
-                interface_link_directories,
-                interface_link_libraries,
+                imported_configurations,
+                imported_implib,
                 location,
+                locations,
                 ..
-            } => Self {
-                component: Some(component),
-                name,
-                kind: ToolchainComponentDependencyNodeKind::Shared { framework: *framework },
-                interface_include_directories: interface_include_directories.as_slice(),
-                interface_link_directories: interface_link_directories.as_slice(),
-                interface_link_libraries: interface_link_libraries.as_slice(),
-                location: Some(*location),
+            } => {
+                implib = locations.select_implib(config, imported_configurations, imported_implib.as_deref());
+                (
+                    ToolchainComponentDependencyNodeKind::Shared { framework: *framework },
+                    Some(locations.select(config, imported_configurations, location.as_ref())),
+                )
             },
             ManifestCMakeInherentTarget::StaticLibrary {
                 framework,
-                interface_include_directories,
-                interface_link_directories,
-                interface_link_libraries,
+                imported_configurations,
                 location,
+                locations,
                 ..
-            } => Self {
-                component: Some(component),
-                name,
-                kind: ToolchainComponentDependencyNodeKind::Static { framework: *framework },
-                interface_include_directories: interface_include_directories.as_slice(),
-                interface_link_directories: interface_link_directories.as_slice(),
-                interface_link_libraries: interface_link_libraries.as_slice(),
-                location: Some(*location),
-            },
-            ManifestCMakeInherentTarget::InterfaceLibrary {
-                interface_include_directories,
-                interface_link_directories,
-                interface_link_libraries,
+            } => (
+                ToolchainComponentDependencyNodeKind::Static { framework: *framework },
+                Some(locations.select(config, imported_configurations, location.as_ref())),
+            ),
+            ManifestCMakeInherentTarget::UnknownLibrary {
+                imported_configurations,
+                location,
+                locations,
                 ..
-            } => Self {
-                component: Some(component),
-                name,
-                kind: ToolchainComponentDependencyNodeKind::Interface,
-                interface_include_directories: interface_include_directories.as_slice(),
-                interface_link_directories: interface_link_directories.as_slice(),
-                interface_link_libraries: interface_link_libraries.as_slice(),
-                location: None,
+            } => {
+                let location = locations.select(config, imported_configurations, location.as_ref());
+                // NOTE: Archives are the only unambiguous case; anything else is assumed to be linked dynamically.
+                let kind = if Utf8Path::new(location)
+                    .extension()
+                    .is_some_and(|ext| ["a", "lib"].contains(&ext))
+                {
+                    ToolchainComponentDependencyNodeKind::Static { framework: false }
+                } else {
+                    ToolchainComponentDependencyNodeKind::Shared { framework: false }
+                };
+                (kind, Some(location))
             },
+        };
+        Self {
+            component: Some(component),
+            name,
+            kind,
+            interface_compile_definitions: interface_compile_definitions.as_slice(),
+            interface_compile_features: interface_compile_features.as_slice(),
+            interface_compile_options: interface_compile_options.as_slice(),
+            interface_include_directories: interface_include_directories.as_slice(),
+            interface_link_directories: interface_link_directories.as_slice(),
+            interface_link_libraries: interface_link_libraries.as_slice(),
+            interface_link_options: interface_link_options.as_slice(),
+            interface_system_include_directories: interface_system_include_directories.as_slice(),
+            location,
+            implib,
+            objects,
         }
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument)]
     pub fn from_manifests(
         name: &'a str,
+        genex_context: &GenexContext<'_>,
         manifests: &'a BTreeMap<ToolchainComponent, ToolchainComponentManifest<'a>>,
     ) -> Result<Self, self::Error> {
         let mut component = None;
@@ -184,57 +341,73 @@ impl<'a> ToolchainComponentDependencyNode<'a> {
                 .and_then(|manifest| manifest.cmake_properties.imported_targets.get(name))
                 .with_context(|| TargetNotFoundInManifestsSnafu { name: name.to_owned() })?;
             if let ManifestCMakeImportedTarget::Inherent { inherent_target, .. } = target {
-                Ok(ToolchainComponentDependencyNode::new(component, name, inherent_target))
+                Ok(ToolchainComponentDependencyNode::new(
+                    component,
+                    name,
+                    inherent_target,
+                    genex_context.config,
+                ))
             } else {
                 Err(Error::TargetNotInherentInManifests { name: name.to_owned() })
             }
         } else {
-            Ok(ToolchainComponentDependencyNode::from(name))
+            Ok(ToolchainComponentDependencyNode::external(name, genex_context.platform))
         }
     }
 
+    /// Frameworks only exist on macOS; elsewhere the flag is ignored.
     #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn is_framework(&self) -> bool {
+    fn is_framework(&self, platform: ToolchainPlatform) -> bool {
         match self.kind {
             ToolchainComponentDependencyNodeKind::Shared { framework }
-            | ToolchainComponentDependencyNodeKind::Static { framework } => framework,
+            | ToolchainComponentDependencyNodeKind::Static { framework } => {
+                framework && platform.sys() == ToolchainSys::Macos
+            },
             _ => false,
         }
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument)]
-    fn expected_extension(&self) -> Option<&'static str> {
+    fn expected_extension(&self, platform: ToolchainPlatform) -> Option<&'static str> {
+        let sys = platform.sys();
         match self.kind {
-            _ if self.is_framework() => Some("framework"),
-            #[cfg(target_os = "linux")]
-            ToolchainComponentDependencyNodeKind::Shared { .. } => Some("so"),
-            #[cfg(target_os = "macos")]
-            ToolchainComponentDependencyNodeKind::Shared { .. } => Some("dylib"),
-            #[cfg(target_os = "windows")]
-            ToolchainComponentDependencyNodeKind::Shared { .. } => Some("dll"),
-            #[cfg(any(target_os = "linux", target_os = "macos"))]
-            ToolchainComponentDependencyNodeKind::Static { .. } => Some("a"),
-            #[cfg(target_os = "windows")]
-            ToolchainComponentDependencyNodeKind::Static { .. } => Some("lib"),
+            _ if self.is_framework(platform) => Some("framework"),
+            ToolchainComponentDependencyNodeKind::Shared { .. } => match sys {
+                ToolchainSys::Linux => Some("so"),
+                ToolchainSys::Macos => Some("dylib"),
+                // NOTE: A DLL is linked through its import library.
+                ToolchainSys::Windows => Some("lib"),
+            },
+            ToolchainComponentDependencyNodeKind::Static { .. } => match sys {
+                ToolchainSys::Linux | ToolchainSys::Macos => Some("a"),
+                ToolchainSys::Windows => Some("lib"),
+            },
             _ => None,
         }
     }
 
-    fn expected_file_name(&self) -> Option<String> {
-        self.expected_extension().map(|ext| format!("lib{}.{ext}", self.name))
+    fn expected_file_name(&self, platform: ToolchainPlatform) -> Option<String> {
+        let prefix = match platform.sys() {
+            _ if self.is_framework(platform) => "",
+            ToolchainSys::Linux | ToolchainSys::Macos => "lib",
+            ToolchainSys::Windows => "",
+        };
+        self.expected_extension(platform)
+            .map(|ext| format!("{prefix}{}.{ext}", self.name))
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument)]
-    pub fn rustc_link_lib_linkage(&self) -> Option<&'static str> {
+    pub fn rustc_link_lib_linkage(&self, platform: ToolchainPlatform) -> Option<&'static str> {
+        let framework = self.is_framework(platform);
         match self.kind {
-            ToolchainComponentDependencyNodeKind::Shared { framework, .. } => {
+            ToolchainComponentDependencyNodeKind::Shared { .. } => {
                 if framework {
                     Some("framework")
                 } else {
                     Some("dylib")
                 }
             },
-            ToolchainComponentDependencyNodeKind::Static { framework, .. } => {
+            ToolchainComponentDependencyNodeKind::Static { .. } => {
                 if framework {
                     Some("framework")
                 } else {
@@ -245,32 +418,48 @@ impl<'a> ToolchainComponentDependencyNode<'a> {
         }
     }
 
-    fn emit_feature_gate(&self, feature_gate: bool) -> Option<syn::Attribute> {
-        let lib = self.name;
-        if feature_gate {
-            Some(syn::parse_quote!(#[cfg(feature = #lib)]))
-        } else {
-            None
+    /// The file to link against, which is the import library of a shared library on Windows (and the target's location
+    /// otherwise).
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn link_location(&self, platform: ToolchainPlatform) -> Option<&'a str> {
+        let windows = platform.sys() == ToolchainSys::Windows;
+        match self.kind {
+            ToolchainComponentDependencyNodeKind::Shared { .. } if windows => self.implib,
+            _ => self.location,
+        }
+    }
+
+    /// The `cargo:rustc-link-arg` directives linking each object of an object library, relative to the toolchain
+    /// `root`.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn cargo_link_objects(&self, root: &Utf8Path) -> Vec<String> {
+        if self.kind != ToolchainComponentDependencyNodeKind::Object {
+            return Vec::new();
         }
+        self.objects
+            .iter()
+            .map(|object| format!("cargo:rustc-link-arg={}", root.join(object)))
+            .collect()
     }
 
+    /// The `cargo:rustc-link-lib` directive linking the target, if it is a library.
     #[cfg_attr(feature = "tracing", tracing::instrument)]
-    pub fn emit_cargo_link_instruction(&self, feature_gate: bool) -> Result<Option<syn::Stmt>, self::Error> {
+    pub fn cargo_link_instruction(&self, platform: ToolchainPlatform) -> Result<Option<String>, self::Error> {
         // Exit early for targets with no linkage (e.g., executables).
-        let Some(linkage) = self.rustc_link_lib_linkage() else {
+        let Some(linkage) = self.rustc_link_lib_linkage(platform) else {
             return Ok(None);
         };
 
-        let feature_gate = self.emit_feature_gate(feature_gate);
-
         // Exit early for targets with no location (e.g., 3rd-party libraries like `m`, `uuid`).
-        let Some(location) = self.location.map(Utf8Path::new) else {
+        let Some(location) = self.link_location(platform).map(Utf8Path::new) else {
+            if self.location.is_some() {
+                return Err(self::Error::TargetImportLibraryNotFound {
+                    name: self.name.to_owned(),
+                });
+            }
             let lib_name = self.name;
-            let rustc_link_lib = format!("cargo:rustc-link-lib={linkage}={lib_name}");
-            return Ok(Some(syn::parse_quote! {
-                #feature_gate
-                println!(#rustc_link_lib);
-            }));
+            return Ok(Some(format!("cargo:rustc-link-lib={linkage}={lib_name}")));
         };
 
         let Some(file_name) = location.file_name() else {
@@ -278,7 +467,7 @@ impl<'a> ToolchainComponentDependencyNode<'a> {
                 name: self.name.to_owned(),
             });
         };
-        let Some(expected_file_name) = self.expected_file_name() else {
+        let Some(expected_file_name) = self.expected_file_name(platform) else {
             return Ok(None);
         };
         let verbatim;
@@ -313,12 +502,22 @@ impl<'a> ToolchainComponentDependencyNode<'a> {
 
         let modifiers = if verbatim { ":+verbatim" } else { "" };
         let lib_name = if verbatim { file_name } else { self.name };
-        let rustc_link_lib = format!("cargo:rustc-link-lib={linkage}{modifiers}={lib_name}");
+        Ok(Some(format!("cargo:rustc-link-lib={linkage}{modifiers}={lib_name}")))
+    }
+}
 
-        Ok(Some(syn::parse_quote! {
-            #feature_gate
-            println!(#rustc_link_lib);
-        }))
+/// The compiler flag selecting the C++ `standard` (e.g., `17` for the `cxx_std_17` compile feature) on `sys`.
+///
+/// MSVC has no flag for standards before C++14 (which it conforms to by default), and only selects C++23 and later
+/// through `/std:c++latest`.
+pub(crate) fn cxx_standard_flag(sys: ToolchainSys, standard: u32) -> String {
+    match sys {
+        ToolchainSys::Linux | ToolchainSys::Macos => format!("-std=c++{standard}"),
+        ToolchainSys::Windows => match standard {
+            .. 14 => String::from("/std:c++14"),
+            14 | 17 | 20 => format!("/std:c++{standard}"),
+            _ => String::from("/std:c++latest"),
+        },
     }
 }
 
@@ -330,6 +529,9 @@ pub enum Error {
     TargetFileNameNotFound {
         name: String,
     },
+    TargetImportLibraryNotFound {
+        name: String,
+    },
     TargetLibraryParentDirNotContainedInLinkLibraries {
         name: String,
         link_libraries: Vec<String>,
@@ -343,19 +545,27 @@ pub enum Error {
 }
 
 #[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Yokeable)]
 pub struct ToolchainAnalysis<'a> {
     pub handle: ToolchainHandle,
+    /// The platform the analysed manifests describe, which determines library naming and linkage conventions.
+    pub platform: ToolchainPlatform,
+    /// The context generator expressions in the manifests' usage requirements are evaluated against.
+    pub genex_context: GenexContext<'a>,
     pub components: BTreeSet<ToolchainComponent>,
     pub components_targets: ToolchainComponentsTargets<'a>,
     pub targets_component: ToolchainTargetsComponent<'a>,
     pub dependencies: ToolchainComponentDependencyGraph<'a>,
     pub external_targets: BTreeSet<&'a str>,
+    /// Module libraries (i.e., loadable plugins) by target name, mapped to their locations.
+    pub plugins: IndexMap<&'a str, &'a str>,
 }
 
 impl<'a> ToolchainAnalysis<'a> {
     #[cfg_attr(feature = "tracing", tracing::instrument)]
     pub fn new(
         handle: ToolchainHandle,
+        genex_context: GenexContext<'a>,
         components: BTreeSet<ToolchainComponent>,
         manifests: &'a BTreeMap<ToolchainComponent, ToolchainComponentManifest<'a>>,
     ) -> Result<Self, self::Error> {
@@ -363,13 +573,18 @@ impl<'a> ToolchainAnalysis<'a> {
         let targets_component = ToolchainTargetsComponent::default();
         let dependencies = ToolchainComponentDependencyGraph::default();
         let external_targets = BTreeSet::new();
+        let plugins = IndexMap::new();
+        let platform = genex_context.platform;
         let mut analysis = Self {
             handle,
+            platform,
+            genex_context,
             components,
             components_targets,
             targets_component,
             dependencies,
             external_targets,
+            plugins,
         };
         analysis
             .analyse_dependencies(manifests)
@@ -383,3 +598,120 @@ impl<'a> ToolchainAnalysis<'a> {
         petgraph::algo::tarjan_scc(&self.dependencies)
     }
 }
+
+/// A [`ToolchainAnalysis`] together with the manifests it borrows from, so that it can be stored, returned from
+/// functions, and sent across threads.
+pub struct OwnedToolchainAnalysis {
+    // NOTE: The cart is erased since an analysis may borrow from either its manifests or a cached snapshot.
+    yoke: Yoke<ToolchainAnalysis<'static>, ErasedArcCart>,
+    manifests: Option<Arc<OwnedToolchainComponentManifests>>,
+}
+
+#[cfg(feature = "debug")]
+impl core::fmt::Debug for OwnedToolchainAnalysis {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OwnedToolchainAnalysis")
+            .field("analysis", self.get())
+            .field("manifests", &self.manifests)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OwnedToolchainAnalysis {
+    /// Analyse all of the components of `manifests`.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn new(
+        handle: ToolchainHandle,
+        genex_context: GenexContext<'static>,
+        manifests: Arc<OwnedToolchainComponentManifests>,
+    ) -> Result<Self, self::Error> {
+        let yoke = Yoke::try_attach_to_cart(Arc::clone(&manifests), |manifests| {
+            let manifests = manifests.get();
+            let components = manifests.keys().copied().collect();
+            ToolchainAnalysis::new(handle, genex_context, components, manifests)
+        })?;
+        Ok(Self {
+            yoke: yoke.erase_arc_cart(),
+            manifests: Some(manifests),
+        })
+    }
+
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn get(&self) -> &ToolchainAnalysis<'_> {
+        self.yoke.get()
+    }
+
+    /// The manifests the analysis was performed on, unless it was restored from a cache.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn manifests(&self) -> Option<&Arc<OwnedToolchainComponentManifests>> {
+        self.manifests.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{manifests, target};
+
+    /// The link directive of the only target of `kind` at `location` (with any `extra` properties) for `platform`.
+    fn link_instruction(
+        platform: ToolchainPlatform,
+        kind: &str,
+        location: &str,
+        extra: &str,
+    ) -> Result<Option<String>, self::Error> {
+        let manifests = manifests(&[target("LLVM", kind, "", location, extra)]);
+        let genex_context = GenexContext::new(platform).config("Release");
+        let node = ToolchainComponentDependencyNode::from_manifests("LLVM", &genex_context, manifests.get())?;
+        node.cargo_link_instruction(platform)
+    }
+
+    #[test]
+    fn link_instruction_linux() {
+        let platform = ToolchainPlatform::X86_64_LINUX_GNU;
+        let instruction = link_instruction(platform, "STATIC_LIBRARY", "lib/libLLVM.a", "").unwrap();
+        assert_eq!(instruction.as_deref(), Some("cargo:rustc-link-lib=static=LLVM"));
+        let instruction = link_instruction(platform, "SHARED_LIBRARY", "lib/libLLVM.so.17", "").unwrap();
+        assert_eq!(instruction.as_deref(), Some("cargo:rustc-link-lib=dylib:+verbatim=libLLVM.so.17"));
+    }
+
+    #[test]
+    fn link_instruction_macos() {
+        let platform = ToolchainPlatform::ARM64_MACOS;
+        let instruction = link_instruction(platform, "STATIC_LIBRARY", "lib/libLLVM.a", "").unwrap();
+        assert_eq!(instruction.as_deref(), Some("cargo:rustc-link-lib=static=LLVM"));
+        let instruction = link_instruction(platform, "SHARED_LIBRARY", "lib/libLLVM.dylib", "").unwrap();
+        assert_eq!(instruction.as_deref(), Some("cargo:rustc-link-lib=dylib=LLVM"));
+    }
+
+    #[test]
+    fn link_instruction_windows() {
+        let platform = ToolchainPlatform::X86_64_WINDOWS_MSVC;
+        let instruction = link_instruction(platform, "STATIC_LIBRARY", "lib/LLVM.lib", "").unwrap();
+        assert_eq!(instruction.as_deref(), Some("cargo:rustc-link-lib=static=LLVM"));
+
+        // NOTE: The DLL itself is never linked against, only its import library.
+        let implib = r#""IMPORTED_IMPLIB_RELEASE": "lib/LLVM.lib""#;
+        let instruction = link_instruction(platform, "SHARED_LIBRARY", "bin/LLVM.dll", implib).unwrap();
+        assert_eq!(instruction.as_deref(), Some("cargo:rustc-link-lib=dylib=LLVM"));
+        let implib = r#""IMPORTED_IMPLIB": "lib/LLVM-17.lib""#;
+        let instruction = link_instruction(platform, "SHARED_LIBRARY", "bin/LLVM.dll", implib).unwrap();
+        assert_eq!(instruction.as_deref(), Some("cargo:rustc-link-lib=dylib:+verbatim=LLVM-17.lib"));
+
+        let Err(error) = link_instruction(platform, "SHARED_LIBRARY", "bin/LLVM.dll", "") else {
+            panic!("expected a shared library without an import library to fail to link");
+        };
+        assert!(matches!(error, self::Error::TargetImportLibraryNotFound { .. }));
+    }
+
+    #[test]
+    fn cxx_standard_flags() {
+        assert_eq!(cxx_standard_flag(ToolchainSys::Linux, 11), "-std=c++11");
+        assert_eq!(cxx_standard_flag(ToolchainSys::Macos, 17), "-std=c++17");
+        assert_eq!(cxx_standard_flag(ToolchainSys::Windows, 11), "/std:c++14");
+        assert_eq!(cxx_standard_flag(ToolchainSys::Windows, 17), "/std:c++17");
+        assert_eq!(cxx_standard_flag(ToolchainSys::Windows, 23), "/std:c++latest");
+    }
+}
@@ -0,0 +1,118 @@
+use indexmap::IndexMap;
+use std::collections::BTreeMap;
+
+use crate::{ToolchainAnalysis, ToolchainComponent};
+
+/// The libraries LLVM builds for each of its target backends (e.g., `LLVMX86CodeGen` for `X86`).
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
+pub enum ToolchainTargetBackendLibrary {
+    AsmParser,
+    CodeGen,
+    Desc,
+    Disassembler,
+    Info,
+}
+
+impl ToolchainTargetBackendLibrary {
+    pub const ALL: [Self; 5] = [
+        Self::AsmParser,
+        Self::CodeGen,
+        Self::Desc,
+        Self::Disassembler,
+        Self::Info,
+    ];
+
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::AsmParser => "AsmParser",
+            Self::CodeGen => "CodeGen",
+            Self::Desc => "Desc",
+            Self::Disassembler => "Disassembler",
+            Self::Info => "Info",
+        }
+    }
+}
+
+/// The libraries of each target backend, keyed by backend name (e.g., `AArch64`).
+pub type ToolchainTargetBackends<'a> = BTreeMap<&'a str, BTreeMap<ToolchainTargetBackendLibrary, &'a str>>;
+
+/// Group the LLVM libraries among `targets` by the target backend they belong to.
+///
+/// A backend is only recognized if both its `Desc` and `Info` libraries are present, which rules out unrelated
+/// libraries sharing a suffix (e.g., `LLVMDebugInfo` or `LLVMMCDisassembler`).
+#[must_use]
+#[cfg_attr(feature = "tracing", tracing::instrument)]
+pub fn target_backends<'a>(
+    targets: impl IntoIterator<Item = &'a str> + crate::LlvmupTracingDebug,
+) -> ToolchainTargetBackends<'a> {
+    let mut candidates = ToolchainTargetBackends::new();
+    for target in targets {
+        let Some(name) = target.strip_prefix("LLVM") else {
+            continue;
+        };
+        for library in ToolchainTargetBackendLibrary::ALL {
+            if let Some(backend) = name
+                .strip_suffix(library.suffix())
+                .filter(|backend| !backend.is_empty())
+            {
+                candidates.entry(backend).or_default().insert(library, target);
+            }
+        }
+    }
+    candidates.retain(|_, libraries| {
+        libraries.contains_key(&ToolchainTargetBackendLibrary::Desc)
+            && libraries.contains_key(&ToolchainTargetBackendLibrary::Info)
+    });
+    candidates
+}
+
+impl<'a> ToolchainAnalysis<'a> {
+    /// The target backends (and their libraries) provided by the analysed LLVM component.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn target_backends(&self) -> ToolchainTargetBackends<'a> {
+        let targets = self
+            .components_targets
+            .get(&ToolchainComponent::Llvm)
+            .into_iter()
+            .flat_map(IndexMap::keys)
+            .copied()
+            .collect::<Vec<_>>();
+        target_backends(targets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_backends_require_desc_and_info() {
+        let backends = target_backends([
+            "LLVMCore",
+            "LLVMDebugInfo",
+            "LLVMMCDisassembler",
+            "LLVMInfo",
+            "LLVMARMCodeGen",
+            "LLVMARMDesc",
+            "LLVMX86AsmParser",
+            "LLVMX86CodeGen",
+            "LLVMX86Desc",
+            "LLVMX86Info",
+            "clangBasic",
+        ]);
+        assert_eq!(backends.keys().collect::<Vec<_>>(), [&"X86"]);
+        assert_eq!(
+            backends["X86"],
+            BTreeMap::from([
+                (ToolchainTargetBackendLibrary::AsmParser, "LLVMX86AsmParser"),
+                (ToolchainTargetBackendLibrary::CodeGen, "LLVMX86CodeGen"),
+                (ToolchainTargetBackendLibrary::Desc, "LLVMX86Desc"),
+                (ToolchainTargetBackendLibrary::Info, "LLVMX86Info"),
+            ])
+        );
+    }
+}
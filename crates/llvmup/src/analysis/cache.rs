@@ -0,0 +1,316 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use snafu::prelude::*;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+use yoke::{Yoke, Yokeable};
+
+use crate::{
+    analysis::{
+        genex::GenexContext,
+        ToolchainComponentDependencyGraph,
+        ToolchainComponentDependencyNode,
+        ToolchainComponentDependencyNodeKind,
+        ToolchainComponentsTargets,
+        ToolchainTargetsComponent,
+    },
+    toolchain::{component::manifest::ManifestCMakeInherentTarget, ToolchainHandle},
+    OwnedToolchainAnalysis,
+    ToolchainAnalysis,
+    ToolchainComponent,
+    ToolchainPlatform,
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    SerdeJsonFromStr {
+        source: serde_json::Error,
+    },
+    SnapshotEdgeOutOfBounds {
+        index: usize,
+    },
+    SnapshotTargetNotFound {
+        component: ToolchainComponent,
+        name: String,
+    },
+}
+
+impl Error {
+    /// Whether the cached snapshot is truncated or otherwise malformed, rather than inconsistent with itself.
+    #[must_use]
+    pub fn is_corrupt(&self) -> bool {
+        matches!(self, Self::SerdeJsonFromStr { .. })
+    }
+}
+
+/// Bumped whenever the layout of [`ToolchainAnalysisSnapshot`] changes, so that stale caches are regenerated.
+const ANALYSIS_CACHE_VERSION: u32 = 2;
+
+/// The key an analysis is cached under: a digest of the cache layout, the generator expression context, and the
+/// sources of the analysed manifests.
+#[must_use]
+#[cfg_attr(feature = "tracing", tracing::instrument)]
+pub fn analysis_cache_key(genex_context: &GenexContext<'_>, sources: &BTreeMap<ToolchainComponent, String>) -> String {
+    let mut hasher = Sha512::new();
+    // NOTE: Every field is length-prefixed so that adjacent fields can't run into each other.
+    let mut update = |bytes: &[u8]| {
+        hasher.update(bytes.len().to_le_bytes());
+        hasher.update(bytes);
+    };
+    update(&ANALYSIS_CACHE_VERSION.to_le_bytes());
+    update(genex_context.config.as_bytes());
+    update(genex_context.compiler_id.as_bytes());
+    update(genex_context.platform.to_string().as_bytes());
+    for (component, source) in sources {
+        update(component.to_string().as_bytes());
+        update(source.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// The serialized form of a [`ToolchainAnalysis`], from which the analysis can be rebuilt without consulting the
+/// manifests or evaluating any generator expressions.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Deserialize, Serialize, Yokeable)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolchainAnalysisSnapshot<'a> {
+    #[serde(borrow)]
+    pub config: Cow<'a, str>,
+    #[serde(borrow)]
+    pub compiler_id: Cow<'a, str>,
+    pub platform: ToolchainPlatform,
+    pub components: BTreeSet<ToolchainComponent>,
+    #[serde(borrow)]
+    pub targets: IndexMap<ToolchainComponent, IndexMap<Cow<'a, str>, ManifestCMakeInherentTarget<'a>>>,
+    #[serde(borrow)]
+    pub external_targets: BTreeSet<Cow<'a, str>>,
+    /// The graph nodes, in insertion order.
+    #[serde(borrow)]
+    pub nodes: Vec<ToolchainAnalysisSnapshotNode<'a>>,
+    /// The graph edges (as indices into `nodes`), in insertion order.
+    pub edges: Vec<(usize, usize)>,
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ToolchainAnalysisSnapshotNode<'a> {
+    Target {
+        component: ToolchainComponent,
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+    },
+    External {
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+        kind: ToolchainComponentDependencyNodeKind,
+    },
+}
+
+impl<'a> ToolchainAnalysis<'a> {
+    /// Capture the analysis in a form that can be serialized and later restored with
+    /// [`ToolchainAnalysis::from_snapshot`].
+    ///
+    /// Only the targets of the analysed components are captured, so restoring fails if the graph references targets
+    /// of components which were loaded but not analysed.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn snapshot(&self) -> ToolchainAnalysisSnapshot<'_> {
+        let targets = self
+            .components_targets
+            .iter()
+            .map(|(component, targets)| {
+                let targets = targets.iter().map(|(name, target)| (Cow::Borrowed(*name), (*target).clone())).collect();
+                (*component, targets)
+            })
+            .collect();
+
+        let indices = self
+            .dependencies
+            .nodes()
+            .enumerate()
+            .map(|(index, node)| (node, index))
+            .collect::<BTreeMap<_, _>>();
+        let nodes = self
+            .dependencies
+            .nodes()
+            .map(|node| match node.component {
+                Some(component) => ToolchainAnalysisSnapshotNode::Target {
+                    component,
+                    name: Cow::Borrowed(node.name),
+                },
+                None => ToolchainAnalysisSnapshotNode::External {
+                    name: Cow::Borrowed(node.name),
+                    kind: node.kind,
+                },
+            })
+            .collect();
+        let edges = self
+            .dependencies
+            .all_edges()
+            .map(|(source, target, ())| (indices[&source], indices[&target]))
+            .collect();
+
+        ToolchainAnalysisSnapshot {
+            config: Cow::Borrowed(self.genex_context.config),
+            compiler_id: Cow::Borrowed(self.genex_context.compiler_id),
+            platform: self.platform,
+            components: self.components.clone(),
+            targets,
+            external_targets: self.external_targets.iter().copied().map(Cow::Borrowed).collect(),
+            nodes,
+            edges,
+        }
+    }
+
+    /// Restore an analysis captured by [`ToolchainAnalysis::snapshot`].
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn from_snapshot(
+        handle: ToolchainHandle,
+        snapshot: &'a ToolchainAnalysisSnapshot<'a>,
+    ) -> Result<Self, self::Error> {
+        let genex_context = GenexContext::new(snapshot.platform)
+            .config(&snapshot.config)
+            .compiler_id(&snapshot.compiler_id);
+
+        let mut components_targets = ToolchainComponentsTargets::default();
+        let mut targets_component = ToolchainTargetsComponent::default();
+        let mut plugins = IndexMap::new();
+        for (component, targets) in &snapshot.targets {
+            for (name, target) in targets {
+                targets_component.insert(name, *component);
+                if let ManifestCMakeInherentTarget::ModuleLibrary {
+                    imported_configurations,
+                    location,
+                    locations,
+                    ..
+                } = target
+                {
+                    let location = locations.select(genex_context.config, imported_configurations, location);
+                    plugins.insert(name.as_ref(), location);
+                }
+            }
+            let targets = targets.iter().map(|(name, target)| (name.as_ref(), target)).collect();
+            components_targets.insert(*component, targets);
+        }
+
+        let nodes = snapshot
+            .nodes
+            .iter()
+            .map(|node| match node {
+                ToolchainAnalysisSnapshotNode::Target { component, name } => {
+                    let target = snapshot
+                        .targets
+                        .get(component)
+                        .and_then(|targets| targets.get(name))
+                        .with_context(|| SnapshotTargetNotFoundSnafu {
+                            component: *component,
+                            name: name.as_ref(),
+                        })?;
+                    Ok(ToolchainComponentDependencyNode::new(
+                        *component,
+                        name,
+                        target,
+                        genex_context.config,
+                    ))
+                },
+                ToolchainAnalysisSnapshotNode::External { name, kind } => {
+                    Ok(ToolchainComponentDependencyNode::external_with_kind(name, *kind))
+                },
+            })
+            .collect::<Result<Vec<_>, self::Error>>()?;
+        let mut dependencies = ToolchainComponentDependencyGraph::default();
+        for node in &nodes {
+            dependencies.add_node(*node);
+        }
+        // NOTE: Adjacency lists are appended to per edge, so replaying the edges in order restores the graph exactly
+        // (and with it the order of the strongly connected components generation depends on).
+        for (source, target) in snapshot.edges.iter().copied() {
+            let node = |index: usize| {
+                nodes
+                    .get(index)
+                    .copied()
+                    .context(SnapshotEdgeOutOfBoundsSnafu { index })
+            };
+            dependencies.add_edge(node(source)?, node(target)?, ());
+        }
+
+        Ok(Self {
+            handle,
+            platform: snapshot.platform,
+            genex_context,
+            components: snapshot.components.clone(),
+            components_targets,
+            targets_component,
+            dependencies,
+            external_targets: snapshot.external_targets.iter().map(AsRef::as_ref).collect(),
+            plugins,
+        })
+    }
+}
+
+impl OwnedToolchainAnalysis {
+    /// Restore an analysis from the JSON serialization of a [`ToolchainAnalysisSnapshot`], which it borrows from.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn from_snapshot_json(handle: ToolchainHandle, json: String) -> Result<Self, self::Error> {
+        let snapshot = Yoke::<ToolchainAnalysisSnapshot<'static>, String>::try_attach_to_cart(json, |json| {
+            serde_json::from_str(json).context(SerdeJsonFromStrSnafu)
+        })?;
+        let yoke = Yoke::try_attach_to_cart(Arc::new(snapshot), |snapshot| {
+            ToolchainAnalysis::from_snapshot(handle, snapshot.get())
+        })?;
+        Ok(Self {
+            yoke: yoke.erase_arc_cart(),
+            manifests: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{manifests, target};
+
+    #[test]
+    fn snapshot_round_trip() {
+        let manifests = manifests(&[
+            target("LLVMSupport", "STATIC_LIBRARY", r#""m", "LLVMDemangle""#, r"lib\\LLVMSupport.lib", ""),
+            target("LLVMDemangle", "STATIC_LIBRARY", r#""LLVMSupport""#, r"lib\\LLVMDemangle.lib", ""),
+            target("LLVMCore", "STATIC_LIBRARY", r#""LLVMSupport""#, r"lib\\LLVMCore.lib", ""),
+            target("Bye", "MODULE_LIBRARY", "", r"lib\\Bye.dll", ""),
+        ]);
+
+        let handle = ToolchainHandle { hash: 0 };
+        let genex_context = GenexContext::new(ToolchainPlatform::X86_64_WINDOWS_MSVC).config("Release");
+        let analysis = OwnedToolchainAnalysis::new(handle, genex_context, manifests).unwrap();
+        let json = serde_json::to_string(&analysis.get().snapshot()).unwrap();
+
+        let restored = OwnedToolchainAnalysis::from_snapshot_json(handle, json.clone()).unwrap();
+        assert!(restored.manifests().is_none());
+        assert_eq!(serde_json::to_string(&restored.get().snapshot()).unwrap(), json);
+
+        let (analysis, restored) = (analysis.get(), restored.get());
+        assert!(restored.platform == analysis.platform);
+        assert_eq!(restored.genex_context.config, "Release");
+        assert_eq!(restored.external_targets, analysis.external_targets);
+        assert_eq!(restored.plugins, analysis.plugins);
+        assert_eq!(restored.plugins.get("Bye"), Some(&r"lib\Bye.dll"));
+        // NOTE: Nodes only implement `Debug` with the `debug` feature.
+        assert!(restored.dependencies.all_edges().eq(analysis.dependencies.all_edges()));
+        assert!(restored.dependencies_postorder_sccs() == analysis.dependencies_postorder_sccs());
+    }
+
+    #[test]
+    fn snapshot_corrupt() {
+        let handle = ToolchainHandle { hash: 0 };
+        let truncated = String::from(r#"{"config": "Rel"#);
+        let Err(err) = OwnedToolchainAnalysis::from_snapshot_json(handle, truncated) else {
+            panic!("expected a truncated snapshot to fail to load");
+        };
+        assert!(err.is_corrupt());
+    }
+}
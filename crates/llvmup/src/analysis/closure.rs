@@ -0,0 +1,252 @@
+use petgraph::visit::{Dfs, Reversed};
+use snafu::prelude::*;
+use std::collections::BTreeSet;
+
+use crate::{
+    analysis::{ToolchainComponentDependencyGraph, ToolchainComponentDependencyNode, ToolchainComponentDependencyNodeKind},
+    ToolchainAnalysis,
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    TargetNotFoundInGraph { name: String },
+}
+
+impl From<self::Error> for crate::Error {
+    fn from(source: self::Error) -> Self {
+        Self::LlvmupAnalysisClosure { source }
+    }
+}
+
+/// A set of targets together with everything they (transitively) depend on, or everything that (transitively)
+/// depends on them.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct ToolchainLinkClosure<'a> {
+    pub nodes: BTreeSet<ToolchainComponentDependencyNode<'a>>,
+    /// The strongly connected components of the closure, dependencies before their dependents.
+    pub postorder_sccs: Vec<Vec<ToolchainComponentDependencyNode<'a>>>,
+}
+
+impl<'a> ToolchainLinkClosure<'a> {
+    /// The closure of the dependencies of the component targets named `roots` (including the roots themselves).
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn dependencies<'t>(
+        graph: &ToolchainComponentDependencyGraph<'a>,
+        roots: impl IntoIterator<Item = &'t str> + crate::LlvmupTracingDebug,
+    ) -> Result<Self, self::Error> {
+        let mut nodes = BTreeSet::new();
+        for root in roots {
+            let mut dfs = Dfs::new(graph, find_target(graph, root)?);
+            while let Some(node) = dfs.next(graph) {
+                nodes.insert(node);
+            }
+        }
+        Ok(Self::new(graph, nodes))
+    }
+
+    /// The closure of the dependents of the targets named `roots` (including the roots themselves).
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn dependents<'t>(
+        graph: &ToolchainComponentDependencyGraph<'a>,
+        roots: impl IntoIterator<Item = &'t str> + crate::LlvmupTracingDebug,
+    ) -> Result<Self, self::Error> {
+        let reversed = Reversed(graph);
+        let mut nodes = BTreeSet::new();
+        for root in roots {
+            // NOTE: External libraries are valid roots here, e.g., to find out what pulls in `zstd`.
+            let root = graph
+                .nodes()
+                .find(|node| node.name == root)
+                .with_context(|| TargetNotFoundInGraphSnafu { name: root })?;
+            let mut dfs = Dfs::new(reversed, root);
+            while let Some(node) = dfs.next(reversed) {
+                nodes.insert(node);
+            }
+        }
+        Ok(Self::new(graph, nodes))
+    }
+
+    fn new(graph: &ToolchainComponentDependencyGraph<'a>, nodes: BTreeSet<ToolchainComponentDependencyNode<'a>>) -> Self {
+        // NOTE: Nodes of an SCC are mutually reachable, so either all or none of them are in a dependency closure.
+        let postorder_sccs = petgraph::algo::tarjan_scc(graph)
+            .into_iter()
+            .filter_map(|mut scc| {
+                scc.retain(|node| nodes.contains(node));
+                (!scc.is_empty()).then_some(scc)
+            })
+            .collect();
+        Self { nodes, postorder_sccs }
+    }
+
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn contains(&self, node: &ToolchainComponentDependencyNode<'a>) -> bool {
+        self.nodes.contains(node)
+    }
+
+    /// The libraries to link, in the order a linker expects them (dependents before their dependencies).
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn libraries(&self) -> Vec<ToolchainComponentDependencyNode<'a>> {
+        self.postorder_sccs
+            .iter()
+            .rev()
+            .flatten()
+            .filter(|node| {
+                matches!(
+                    node.kind,
+                    ToolchainComponentDependencyNodeKind::Object
+                        | ToolchainComponentDependencyNodeKind::Shared { .. }
+                        | ToolchainComponentDependencyNodeKind::Static { .. }
+                )
+            })
+            .copied()
+            .collect()
+    }
+
+    /// The system libraries (i.e., those not provided by any component) to link, in link order.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn external_libraries(&self) -> Vec<&'a str> {
+        self.libraries()
+            .into_iter()
+            .filter(|node| node.component.is_none())
+            .map(|node| node.name)
+            .collect()
+    }
+
+    /// The include directories (relative to the toolchain root) required to compile against the closure.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn include_directories(&self) -> BTreeSet<&'a str> {
+        self.nodes
+            .iter()
+            .flat_map(|node| node.interface_include_directories.iter())
+            .collect()
+    }
+
+    /// Like [`ToolchainLinkClosure::include_directories`], but for those to be searched as system directories.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn system_include_directories(&self) -> BTreeSet<&'a str> {
+        self.nodes
+            .iter()
+            .flat_map(|node| node.interface_system_include_directories.iter())
+            .collect()
+    }
+}
+
+fn find_target<'a>(
+    graph: &ToolchainComponentDependencyGraph<'a>,
+    name: &str,
+) -> Result<ToolchainComponentDependencyNode<'a>, self::Error> {
+    graph
+        .nodes()
+        .find(|node| node.component.is_some() && node.name == name)
+        .with_context(|| TargetNotFoundInGraphSnafu { name })
+}
+
+impl ToolchainAnalysis<'_> {
+    /// What is needed to link the component targets named `roots`.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn link_closure<'t>(
+        &self,
+        roots: impl IntoIterator<Item = &'t str> + crate::LlvmupTracingDebug,
+    ) -> Result<ToolchainLinkClosure<'_>, self::Error> {
+        ToolchainLinkClosure::dependencies(&self.dependencies, roots)
+    }
+
+    /// Everything that (transitively) depends on the targets named `roots`.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn reverse_dependencies<'t>(
+        &self,
+        roots: impl IntoIterator<Item = &'t str> + crate::LlvmupTracingDebug,
+    ) -> Result<ToolchainLinkClosure<'_>, self::Error> {
+        ToolchainLinkClosure::dependents(&self.dependencies, roots)
+    }
+}
+
+#[cfg(all(test, feature = "analysis"))]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{analysis, manifests, target},
+        OwnedToolchainAnalysis,
+        ToolchainPlatform,
+    };
+
+    /// `LLVMOption` links `LLVMCore`, which links `LLVMRemarks` and `LLVMSupport` (in a cycle with `LLVMDemangle`),
+    /// which links the system library `z`.
+    fn closure_analysis() -> OwnedToolchainAnalysis {
+        let manifests = manifests(&[
+            target("LLVMOption", "STATIC_LIBRARY", r#""LLVMCore""#, "lib/libLLVMOption.a", ""),
+            target(
+                "LLVMCore",
+                "STATIC_LIBRARY",
+                r#""LLVMRemarks", "LLVMSupport""#,
+                "lib/libLLVMCore.a",
+                r#""INTERFACE_INCLUDE_DIRECTORIES": ["include/core"]"#,
+            ),
+            target("LLVMRemarks", "STATIC_LIBRARY", r#""LLVMSupport""#, "lib/libLLVMRemarks.a", ""),
+            target(
+                "LLVMSupport",
+                "STATIC_LIBRARY",
+                r#""LLVMDemangle", "z""#,
+                "lib/libLLVMSupport.a",
+                r#""INTERFACE_INCLUDE_DIRECTORIES": ["include"],
+                "INTERFACE_SYSTEM_INCLUDE_DIRECTORIES": ["include/system"]"#,
+            ),
+            target("LLVMDemangle", "STATIC_LIBRARY", r#""LLVMSupport""#, "lib/libLLVMDemangle.a", ""),
+        ]);
+        analysis(ToolchainPlatform::X86_64_LINUX_GNU, manifests)
+    }
+
+    #[test]
+    fn link_closure_dependencies() {
+        let analysis = closure_analysis();
+        let closure = analysis.get().link_closure(["LLVMCore"]).unwrap();
+        let mut actual = closure.nodes.iter().map(|node| node.name).collect::<Vec<_>>();
+        actual.sort_unstable();
+        assert_eq!(actual, ["LLVMCore", "LLVMDemangle", "LLVMRemarks", "LLVMSupport", "z"]);
+
+        // NOTE: Dependents come before their dependencies, and the members of a cycle are adjacent.
+        let libraries = closure.libraries().iter().map(|node| node.name).collect::<Vec<_>>();
+        let position = |name: &str| libraries.iter().position(|library| *library == name).unwrap();
+        assert_eq!(libraries.len(), 5);
+        assert_eq!(position("LLVMCore"), 0);
+        assert!(position("LLVMRemarks") < position("LLVMSupport"));
+        assert_eq!(position("LLVMSupport").abs_diff(position("LLVMDemangle")), 1);
+        assert_eq!(position("z"), 4);
+
+        assert_eq!(closure.external_libraries(), ["z"]);
+        assert_eq!(closure.include_directories(), BTreeSet::from(["include", "include/core"]));
+        assert_eq!(closure.system_include_directories(), BTreeSet::from(["include/system"]));
+    }
+
+    #[test]
+    fn link_closure_rejects_external_roots() {
+        let analysis = closure_analysis();
+        assert!(matches!(
+            analysis.get().link_closure(["z"]),
+            Err(Error::TargetNotFoundInGraph { name }) if name == "z"
+        ));
+        assert!(matches!(
+            analysis.get().link_closure(["LLVMUnknown"]),
+            Err(Error::TargetNotFoundInGraph { .. })
+        ));
+    }
+
+    #[test]
+    fn reverse_dependencies() {
+        let analysis = closure_analysis();
+        let closure = analysis.get().reverse_dependencies(["LLVMRemarks"]).unwrap();
+        let mut actual = closure.nodes.iter().map(|node| node.name).collect::<Vec<_>>();
+        actual.sort_unstable();
+        assert_eq!(actual, ["LLVMCore", "LLVMOption", "LLVMRemarks"]);
+
+        // NOTE: Unlike dependency closures, external libraries are valid roots.
+        let closure = analysis.get().reverse_dependencies(["z"]).unwrap();
+        assert_eq!(closure.nodes.len(), 6);
+        assert_eq!(closure.external_libraries(), ["z"]);
+    }
+}
@@ -1,6 +1,6 @@
 use indexmap::IndexMap;
 use snafu::prelude::*;
-use std::collections::BTreeMap;
+use std::{borrow::Cow, collections::BTreeMap};
 
 #[cfg(feature = "ahash")]
 use ahash::AHashMap;
@@ -16,9 +16,11 @@ use crate::{
     ToolchainComponentManifest,
 };
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Snafu)]
 pub enum Error {
     LlvmupComponentNotLoaded { component: ToolchainComponent },
+    LlvmupGenex { source: crate::analysis::genex::Error },
 }
 
 impl<'a> ToolchainAnalysis<'a> {
@@ -45,34 +47,72 @@ impl<'a> ToolchainAnalysis<'a> {
                 .cmake_properties
                 .imported_targets
                 .iter()
-                .map(|(name, target)| (*name, target))
+                .map(|(name, target)| (name.as_ref(), target))
             {
                 if let ManifestCMakeImportedTarget::Inherent { inherent_target, .. } = target {
                     self.targets_component.insert(name, component);
+                    if let ManifestCMakeInherentTarget::ModuleLibrary {
+                        imported_configurations,
+                        location,
+                        locations,
+                        ..
+                    } = inherent_target
+                    {
+                        let location = locations.select(self.genex_context.config, imported_configurations, location);
+                        self.plugins.insert(name, location);
+                    }
                     if let ManifestCMakeInherentTarget::InterfaceLibrary {
                         interface_link_libraries,
                         ..
                     }
+                    | ManifestCMakeInherentTarget::ObjectLibrary {
+                        interface_link_libraries,
+                        ..
+                    }
                     | ManifestCMakeInherentTarget::StaticLibrary {
                         interface_link_libraries,
                         ..
+                    }
+                    | ManifestCMakeInherentTarget::UnknownLibrary {
+                        interface_link_libraries,
+                        ..
                     } = inherent_target
                     {
-                        let node = ToolchainComponentDependencyNode::new(component, name, inherent_target);
+                        let node = ToolchainComponentDependencyNode::new(
+                            component,
+                            name,
+                            inherent_target,
+                            self.genex_context.config,
+                        );
                         let node = self.dependencies.add_node(node);
-                        for lib in interface_link_libraries.iter().map(|library| {
-                            library
-                                .strip_prefix("$<LINK_ONLY:")
-                                .and_then(|library| library.strip_suffix('>'))
-                                .unwrap_or(library)
-                        }) {
-                            let lib_node = memo.entry(lib).or_insert_with(|| {
-                                ToolchainComponentDependencyNode::from_manifests(lib, manifests).unwrap()
-                            });
-                            let lib_node = self.dependencies.add_node(*lib_node);
-                            self.dependencies.add_edge(node, lib_node, ());
-                            if lib_node.component.is_none() {
-                                self.external_targets.insert(lib);
+                        for library in interface_link_libraries.iter() {
+                            for lib in self
+                                .genex_context
+                                .evaluate_list_item(library)
+                                .context(LlvmupGenexSnafu)?
+                            {
+                                let lib = match lib {
+                                    Cow::Borrowed(lib) => lib,
+                                    // NOTE: Graph nodes borrow from the manifests, so a computed name can only be
+                                    // used if it names a target of one of them.
+                                    Cow::Owned(lib) => {
+                                        let Some(lib) = manifests_target_name(manifests, &lib) else {
+                                            #[cfg(feature = "tracing")]
+                                            tracing::warn!(library, lib, "dropping a computed non-target library");
+                                            continue;
+                                        };
+                                        lib
+                                    },
+                                };
+                                let lib_node = memo.entry(lib).or_insert_with(|| {
+                                    ToolchainComponentDependencyNode::from_manifests(lib, &self.genex_context, manifests)
+                                        .unwrap()
+                                });
+                                let lib_node = self.dependencies.add_node(*lib_node);
+                                self.dependencies.add_edge(node, lib_node, ());
+                                if lib_node.component.is_none() {
+                                    self.external_targets.insert(lib);
+                                }
                             }
                         }
                     }
@@ -86,3 +126,44 @@ impl<'a> ToolchainAnalysis<'a> {
         Ok(())
     }
 }
+
+/// The name of a target of `manifests`, borrowed from the manifests.
+fn manifests_target_name<'a>(
+    manifests: &'a BTreeMap<ToolchainComponent, ToolchainComponentManifest<'a>>,
+    name: &str,
+) -> Option<&'a str> {
+    manifests
+        .values()
+        .find_map(|manifest| manifest.cmake_properties.imported_targets.get_key_value(name))
+        .map(|(name, _)| name.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::{analysis, manifests, target},
+        ToolchainPlatform,
+    };
+
+    #[test]
+    fn analyse_dependencies_drops_unrepresentable() {
+        let manifests = manifests(&[
+            target(
+                "LLVMCore",
+                "STATIC_LIBRARY",
+                r#""LLVMSupport", "$<TARGET_PROPERTY:x,y>", "lib$<1:m>", "$<1:LLVM>Support""#,
+                "lib/libLLVMCore.a",
+                "",
+            ),
+            target("LLVMSupport", "STATIC_LIBRARY", "", "lib/libLLVMSupport.a", ""),
+        ]);
+        let analysis = analysis(ToolchainPlatform::X86_64_LINUX_GNU, manifests);
+        let edges = analysis
+            .get()
+            .dependencies
+            .all_edges()
+            .map(|(source, target, ())| (source.name, target.name))
+            .collect::<Vec<_>>();
+        assert_eq!(edges, [("LLVMCore", "LLVMSupport")]);
+    }
+}
@@ -0,0 +1,314 @@
+use core::fmt::Write;
+use petgraph::visit::{Dfs, Walker};
+use serde::Serialize;
+use snafu::prelude::*;
+use std::collections::BTreeMap;
+
+use crate::{
+    analysis::{ToolchainComponentDependencyGraph, ToolchainComponentDependencyNode, ToolchainComponentDependencyNodeKind},
+    ToolchainAnalysis,
+    ToolchainComponent,
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    SerdeJsonToString { source: serde_json::Error },
+    TargetNotFoundInGraph { name: String },
+}
+
+impl From<self::Error> for crate::Error {
+    fn from(source: self::Error) -> Self {
+        Self::LlvmupAnalysisExport { source }
+    }
+}
+
+/// The formats a dependency graph can be exported to.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum ToolchainGraphFormat {
+    /// Graphviz DOT.
+    Dot,
+    /// A JSON object with `nodes`, `edges` and `sccs` arrays.
+    Json,
+    /// A Mermaid flowchart.
+    Mermaid,
+}
+
+impl ToolchainComponentDependencyNodeKind {
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Executable => "executable",
+            Self::Interface => "interface",
+            Self::Module => "module",
+            Self::Object => "object",
+            Self::Shared { framework: true } | Self::Static { framework: true } => "framework",
+            Self::Shared { framework: false } => "shared",
+            Self::Static { framework: false } => "static",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonGraph<'a> {
+    nodes: Vec<JsonNode<'a>>,
+    edges: Vec<(usize, usize)>,
+    sccs: Vec<Vec<usize>>,
+}
+
+#[derive(Serialize)]
+struct JsonNode<'a> {
+    name: &'a str,
+    component: Option<ToolchainComponent>,
+    kind: &'static str,
+    external: bool,
+    /// The index (into `sccs`) of the dependency cycle the node is part of, if any.
+    scc: Option<usize>,
+}
+
+/// The (sub)graph being exported, with nodes numbered in graph order.
+struct ExportGraph<'a> {
+    nodes: Vec<ToolchainComponentDependencyNode<'a>>,
+    edges: Vec<(usize, usize)>,
+    /// Dependency cycles (i.e., strongly connected components of more than one node).
+    sccs: Vec<Vec<usize>>,
+    node_scc: BTreeMap<usize, usize>,
+}
+
+impl<'a> ExportGraph<'a> {
+    fn new(graph: &ToolchainComponentDependencyGraph<'a>, roots: &[&str]) -> Result<Self, self::Error> {
+        let nodes = if roots.is_empty() {
+            graph.nodes().collect::<Vec<_>>()
+        } else {
+            let mut reachable = std::collections::BTreeSet::new();
+            for root in roots {
+                let node = graph
+                    .nodes()
+                    .find(|node| node.name == *root)
+                    .with_context(|| TargetNotFoundInGraphSnafu { name: *root })?;
+                reachable.extend(Dfs::new(graph, node).iter(graph));
+            }
+            graph.nodes().filter(|node| reachable.contains(node)).collect()
+        };
+        let indices = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (*node, index))
+            .collect::<BTreeMap<_, _>>();
+        let edges = graph
+            .all_edges()
+            .filter_map(|(source, target, ())| Some((*indices.get(&source)?, *indices.get(&target)?)))
+            .collect();
+        let sccs = petgraph::algo::tarjan_scc(graph)
+            .into_iter()
+            .filter(|scc| scc.len() > 1)
+            .filter_map(|scc| scc.iter().map(|node| indices.get(node).copied()).collect::<Option<Vec<_>>>())
+            .collect::<Vec<_>>();
+        let node_scc = sccs
+            .iter()
+            .enumerate()
+            .flat_map(|(scc, nodes)| nodes.iter().map(move |node| (*node, scc)))
+            .collect();
+        Ok(Self {
+            nodes,
+            edges,
+            sccs,
+            node_scc,
+        })
+    }
+
+    fn label(node: &ToolchainComponentDependencyNode<'_>) -> String {
+        let kind = node.kind.as_str();
+        match node.component {
+            Some(component) => format!("{}\\n{component} {kind}", node.name),
+            None => format!("{}\\nexternal {kind}", node.name),
+        }
+    }
+
+    fn emit_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n    node [shape=box];\n");
+        let quote = |text: &str| text.replace('"', "\\\"");
+        let emit_node = |dot: &mut String, indent: &str, index: usize| {
+            let node = &self.nodes[index];
+            let style = if node.component.is_none() { ", style=dashed" } else { "" };
+            let label = quote(&Self::label(node));
+            writeln!(dot, "{indent}n{index} [label=\"{label}\"{style}];").unwrap();
+        };
+        for (index, _) in self.nodes.iter().enumerate() {
+            if !self.node_scc.contains_key(&index) {
+                emit_node(&mut dot, "    ", index);
+            }
+        }
+        for (scc, nodes) in self.sccs.iter().enumerate() {
+            writeln!(dot, "    subgraph cluster_scc{scc} {{").unwrap();
+            writeln!(dot, "        label=\"cycle {scc}\";\n        style=filled;\n        color=lightpink;").unwrap();
+            for index in nodes {
+                emit_node(&mut dot, "        ", *index);
+            }
+            dot.push_str("    }\n");
+        }
+        for (source, target) in &self.edges {
+            writeln!(dot, "    n{source} -> n{target};").unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn emit_json(&self) -> Result<String, self::Error> {
+        let nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| JsonNode {
+                name: node.name,
+                component: node.component,
+                kind: node.kind.as_str(),
+                external: node.component.is_none(),
+                scc: self.node_scc.get(&index).copied(),
+            })
+            .collect();
+        let graph = JsonGraph {
+            nodes,
+            edges: self.edges.clone(),
+            sccs: self.sccs.clone(),
+        };
+        serde_json::to_string_pretty(&graph).context(SerdeJsonToStringSnafu)
+    }
+
+    fn emit_mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart LR\n");
+        let quote = |text: &str| text.replace('"', "#quot;").replace("\\n", "<br>");
+        let emit_node = |mermaid: &mut String, indent: &str, index: usize| {
+            let node = &self.nodes[index];
+            let label = quote(&Self::label(node));
+            let class = if node.component.is_none() { ":::external" } else { "" };
+            writeln!(mermaid, "{indent}n{index}[\"{label}\"]{class}").unwrap();
+        };
+        for (index, _) in self.nodes.iter().enumerate() {
+            if !self.node_scc.contains_key(&index) {
+                emit_node(&mut mermaid, "    ", index);
+            }
+        }
+        for (scc, nodes) in self.sccs.iter().enumerate() {
+            writeln!(mermaid, "    subgraph scc{scc} [\"cycle {scc}\"]").unwrap();
+            for index in nodes {
+                emit_node(&mut mermaid, "        ", *index);
+            }
+            mermaid.push_str("    end\n");
+            writeln!(mermaid, "    style scc{scc} fill:#fdd").unwrap();
+        }
+        for (source, target) in &self.edges {
+            writeln!(mermaid, "    n{source} --> n{target}").unwrap();
+        }
+        mermaid.push_str("    classDef external stroke-dasharray: 4 4\n");
+        mermaid
+    }
+}
+
+impl ToolchainAnalysis<'_> {
+    /// Export the dependency graph, or only the part of it reachable from `roots` (if any are given), in `format`.
+    ///
+    /// Nodes are annotated with their component, kind, and whether they're external, and dependency cycles are
+    /// highlighted.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn export_graph(&self, format: ToolchainGraphFormat, roots: &[&str]) -> Result<String, self::Error> {
+        let graph = ExportGraph::new(&self.dependencies, roots)?;
+        match format {
+            ToolchainGraphFormat::Dot => Ok(graph.emit_dot()),
+            ToolchainGraphFormat::Json => graph.emit_json(),
+            ToolchainGraphFormat::Mermaid => Ok(graph.emit_mermaid()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "analysis"))]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{analysis, manifests, target},
+        OwnedToolchainAnalysis,
+        ToolchainPlatform,
+    };
+
+    /// `LLVM"Core` links `LLVMSupport` (in a cycle with `LLVMDemangle`) and the external `pthread`.
+    fn cyclic_analysis() -> OwnedToolchainAnalysis {
+        let manifests = manifests(&[
+            target(
+                r#"LLVM\"Core"#,
+                "STATIC_LIBRARY",
+                r#""LLVMSupport", "pthread""#,
+                "lib/libLLVMCore.a",
+                "",
+            ),
+            target("LLVMSupport", "STATIC_LIBRARY", r#""LLVMDemangle""#, "lib/libLLVMSupport.a", ""),
+            target("LLVMDemangle", "STATIC_LIBRARY", r#""LLVMSupport""#, "lib/libLLVMDemangle.a", ""),
+        ]);
+        analysis(ToolchainPlatform::X86_64_LINUX_GNU, manifests)
+    }
+
+    #[test]
+    fn export_dot() {
+        let analysis = cyclic_analysis();
+        let dot = analysis.get().export_graph(ToolchainGraphFormat::Dot, &[]).unwrap();
+        assert!(dot.contains(r#"[label="LLVM\"Core\nllvm static"];"#));
+        assert!(dot.contains("pthread\\nexternal") && dot.contains("style=dashed"));
+        assert_eq!(dot.matches("subgraph cluster_scc").count(), 1);
+        assert_eq!(dot.matches(" -> ").count(), 4);
+    }
+
+    #[test]
+    fn export_dot_rooted() {
+        let analysis = cyclic_analysis();
+        let dot = analysis
+            .get()
+            .export_graph(ToolchainGraphFormat::Dot, &["LLVMSupport"])
+            .unwrap();
+        assert!(dot.contains("LLVMSupport") && dot.contains("LLVMDemangle"));
+        assert!(!dot.contains("Core") && !dot.contains("pthread"));
+        assert_eq!(dot.matches("subgraph cluster_scc").count(), 1);
+        assert_eq!(dot.matches(" -> ").count(), 2);
+    }
+
+    #[test]
+    fn export_mermaid() {
+        let analysis = cyclic_analysis();
+        let mermaid = analysis.get().export_graph(ToolchainGraphFormat::Mermaid, &[]).unwrap();
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains(r#"["LLVM#quot;Core<br>llvm static"]"#));
+        assert!(!mermaid.contains(r#"\""#));
+        assert_eq!(mermaid.matches("subgraph scc").count(), 1);
+        assert_eq!(mermaid.matches(" --> ").count(), 4);
+    }
+
+    #[test]
+    fn export_json() {
+        let analysis = cyclic_analysis();
+        let json = analysis.get().export_graph(ToolchainGraphFormat::Json, &[]).unwrap();
+        let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        let nodes = json["nodes"].as_array().unwrap();
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(json["edges"].as_array().unwrap().len(), 4);
+        let sccs = json["sccs"].as_array().unwrap();
+        assert_eq!(sccs.len(), 1);
+        assert_eq!(sccs[0].as_array().unwrap().len(), 2);
+
+        let node = |name: &str| nodes.iter().find(|node| node["name"] == name).unwrap();
+        assert_eq!(node("pthread")["external"], true);
+        assert_eq!(node("LLVM\"Core")["external"], false);
+        assert_eq!(node("LLVM\"Core")["kind"], "static");
+        assert!(node("LLVM\"Core")["scc"].is_null());
+        assert_eq!(node("LLVMSupport")["scc"], 0);
+        assert_eq!(node("LLVMDemangle")["scc"], 0);
+    }
+
+    #[test]
+    fn export_unknown_root() {
+        let analysis = cyclic_analysis();
+        let err = analysis
+            .get()
+            .export_graph(ToolchainGraphFormat::Json, &["LLVMUnknown"])
+            .unwrap_err();
+        assert!(matches!(err, Error::TargetNotFoundInGraph { name } if name == "LLVMUnknown"));
+    }
+}
@@ -0,0 +1,411 @@
+use snafu::prelude::*;
+use std::borrow::Cow;
+
+use crate::{toolchain::platform::ToolchainSys, ToolchainPlatform};
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Snafu)]
+pub enum Error {
+    GenexArgumentCount {
+        name: String,
+        expected: usize,
+        actual: usize,
+    },
+    GenexConditionNotBoolean {
+        value: String,
+    },
+    GenexUnknownExpression {
+        name: String,
+    },
+    GenexUnterminated {
+        input: String,
+    },
+}
+
+/// The values `CMake` generator expressions are evaluated against.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, Copy)]
+pub struct GenexContext<'a> {
+    pub config: &'a str,
+    pub platform: ToolchainPlatform,
+    pub compiler_id: &'a str,
+}
+
+impl<'a> GenexContext<'a> {
+    /// A context for consuming an installed `Release` build for `platform` with that platform's usual compiler.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn new(platform: ToolchainPlatform) -> Self {
+        let compiler_id = match platform.sys() {
+            ToolchainSys::Linux => "GNU",
+            ToolchainSys::Macos => "AppleClang",
+            ToolchainSys::Windows => "MSVC",
+        };
+        Self {
+            config: "Release",
+            platform,
+            compiler_id,
+        }
+    }
+
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn config(mut self, config: &'a str) -> Self {
+        self.config = config;
+        self
+    }
+
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn compiler_id(mut self, compiler_id: &'a str) -> Self {
+        self.compiler_id = compiler_id;
+        self
+    }
+
+    /// The value of `CMAKE_SYSTEM_NAME` for the context's platform, as compared by `$<PLATFORM_ID:...>`.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn platform_id(&self) -> &'static str {
+        match self.platform.sys() {
+            ToolchainSys::Linux => "Linux",
+            ToolchainSys::Macos => "Darwin",
+            ToolchainSys::Windows => "Windows",
+        }
+    }
+
+    /// Evaluate a single entry of a list-valued property (e.g., `INTERFACE_LINK_LIBRARIES`) into the (possibly
+    /// empty) list of items it denotes.
+    ///
+    /// An item depending on what is only known within a `CMake` build (e.g., `$<COMPILE_LANGUAGE:...>` or
+    /// `$<TARGET_PROPERTY:...>`) is dropped with a warning rather than failing the whole evaluation.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn evaluate_list_item(&self, input: &'a str) -> Result<Vec<Cow<'a, str>>, self::Error> {
+        let value = match self.evaluate(input) {
+            Ok(value) => value,
+            Err(error @ self::Error::GenexUnknownExpression { .. }) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(input, %error, "dropping an item with an unsupported generator expression");
+                #[cfg(not(feature = "tracing"))]
+                drop(error);
+                return Ok(Vec::new());
+            },
+            Err(error) => return Err(error),
+        };
+        let items = match value {
+            Cow::Borrowed(value) => value
+                .split(';')
+                .filter(|item| !item.is_empty())
+                .map(Cow::Borrowed)
+                .collect(),
+            Cow::Owned(value) => value
+                .split(';')
+                .filter(|item| !item.is_empty())
+                .map(|item| Cow::Owned(item.to_owned()))
+                .collect(),
+        };
+        Ok(items)
+    }
+
+    /// Evaluate `input`, borrowing from it (or the context) whenever the result is a contiguous piece of either.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn evaluate(&self, input: &'a str) -> Result<Cow<'a, str>, self::Error> {
+        // NOTE: Fast path for the overwhelmingly common case of a plain target or library name.
+        if !input.contains("$<") {
+            return Ok(Cow::Borrowed(input));
+        }
+        let mut parser = GenexParser { input, offset: 0 };
+        let nodes = parser.parse_sequence(&[])?;
+        ensure!(parser.offset == input.len(), GenexUnterminatedSnafu { input });
+        self.evaluate_nodes(&nodes)
+    }
+
+    fn evaluate_nodes(&self, nodes: &[GenexNode<'a>]) -> Result<Cow<'a, str>, self::Error> {
+        let mut result = Cow::Borrowed("");
+        for node in nodes {
+            let value = match node {
+                GenexNode::Text(text) => Cow::Borrowed(*text),
+                GenexNode::Expr { name, args } => self.evaluate_expr(name, args.as_deref())?,
+            };
+            result = concat(result, value);
+        }
+        Ok(result)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn evaluate_expr(
+        &self,
+        name: &[GenexNode<'a>],
+        args: Option<&[Vec<GenexNode<'a>>]>,
+    ) -> Result<Cow<'a, str>, self::Error> {
+        let name = self.evaluate_nodes(name)?;
+        let bool_str = |value: bool| Cow::Borrowed(if value { "1" } else { "0" });
+
+        // NOTE: The conditional and wrapping forms take the remainder verbatim, commas included.
+        let verbatim = |args: &[Vec<GenexNode<'a>>]| -> Result<Cow<'a, str>, self::Error> {
+            let mut result = Cow::Borrowed("");
+            for (index, arg) in args.iter().enumerate() {
+                if index > 0 {
+                    result = concat(result, Cow::Borrowed(","));
+                }
+                result = concat(result, self.evaluate_nodes(arg)?);
+            }
+            Ok(result)
+        };
+        match (name.as_ref(), args) {
+            // NOTE: Only exported (installed) usage requirements are ever analysed, so `BUILD_INTERFACE` is dropped.
+            ("0" | "BUILD_INTERFACE", _) | ("1", None) => return Ok(Cow::Borrowed("")),
+            ("1" | "INSTALL_INTERFACE" | "LINK_ONLY" | "TARGET_NAME", Some(args)) => return verbatim(args),
+            ("IF", Some([condition, then, otherwise])) => {
+                let branch = if parse_bool(&self.evaluate_nodes(condition)?)? {
+                    then
+                } else {
+                    otherwise
+                };
+                return self.evaluate_nodes(branch);
+            },
+            ("ANGLE-R", None) => return Ok(Cow::Borrowed(">")),
+            ("COMMA", None) => return Ok(Cow::Borrowed(",")),
+            ("SEMICOLON", None) => return Ok(Cow::Borrowed(";")),
+            ("CONFIG", None) => return Ok(Cow::Borrowed(self.config)),
+            ("PLATFORM_ID", None) => return Ok(Cow::Borrowed(self.platform_id())),
+            ("C_COMPILER_ID" | "CXX_COMPILER_ID", None) => return Ok(Cow::Borrowed(self.compiler_id)),
+            // NOTE: Consumers link through rustc, which always drives the link as for C++ objects at most.
+            ("LINK_LANGUAGE", None) => return Ok(Cow::Borrowed("CXX")),
+            _ => {},
+        }
+
+        let args = args
+            .map(|args| {
+                args.iter()
+                    .map(|arg| self.evaluate_nodes(arg))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let args = args.iter().map(AsRef::as_ref).collect::<Vec<&str>>();
+        let arity = |expected: usize| {
+            ensure!(args.len() == expected, GenexArgumentCountSnafu {
+                name: name.as_ref(),
+                expected,
+                actual: args.len(),
+            });
+            Ok(())
+        };
+
+        let value = match name.as_ref() {
+            "BOOL" => {
+                arity(1)?;
+                bool_str(is_truthy(args[0]))
+            },
+            "AND" => bool_str(args.iter().try_fold(true, |acc, arg| Ok(acc & parse_bool(arg)?))?),
+            "OR" => bool_str(args.iter().try_fold(false, |acc, arg| Ok(acc | parse_bool(arg)?))?),
+            "NOT" => {
+                arity(1)?;
+                bool_str(!parse_bool(args[0])?)
+            },
+            "IF" => {
+                // NOTE: The well-formed case is handled above.
+                arity(3)?;
+                Cow::Borrowed("")
+            },
+            "STREQUAL" => {
+                arity(2)?;
+                bool_str(args[0] == args[1])
+            },
+            "EQUAL" => {
+                arity(2)?;
+                bool_str(
+                    args[0]
+                        .parse::<i64>()
+                        .ok()
+                        .is_some_and(|lhs| Ok(lhs) == args[1].parse::<i64>()),
+                )
+            },
+            "IN_LIST" => {
+                arity(2)?;
+                bool_str(args[1].split(';').any(|item| item == args[0]))
+            },
+            "CONFIG" => bool_str(args.iter().any(|config| config.eq_ignore_ascii_case(self.config))),
+            "PLATFORM_ID" => bool_str(args.iter().any(|id| *id == self.platform_id())),
+            "C_COMPILER_ID" | "CXX_COMPILER_ID" => bool_str(args.contains(&self.compiler_id)),
+            "LINK_LANGUAGE" => bool_str(args.iter().any(|lang| matches!(*lang, "C" | "CXX"))),
+            "LINK_LIBRARY" | "LINK_GROUP" => Cow::Owned(args.get(1 ..).unwrap_or_default().join(";")),
+            name => return GenexUnknownExpressionSnafu { name }.fail(),
+        };
+        Ok(value)
+    }
+}
+
+enum GenexNode<'a> {
+    Text(&'a str),
+    Expr {
+        name: Vec<GenexNode<'a>>,
+        /// The arguments, split at top-level commas.
+        args: Option<Vec<Vec<GenexNode<'a>>>>,
+    },
+}
+
+struct GenexParser<'a> {
+    input: &'a str,
+    offset: usize,
+}
+
+impl<'a> GenexParser<'a> {
+    /// Parse text and nested expressions up to (but not including) an unmatched `>` or any of `stops`.
+    fn parse_sequence(&mut self, stops: &[char]) -> Result<Vec<GenexNode<'a>>, self::Error> {
+        let mut nodes = vec![];
+        let mut text_start = self.offset;
+        while let Some(rest) = self.input.get(self.offset ..) {
+            if rest.starts_with("$<") {
+                if text_start < self.offset {
+                    nodes.push(GenexNode::Text(&self.input[text_start .. self.offset]));
+                }
+                self.offset += 2;
+                nodes.push(self.parse_expr()?);
+                text_start = self.offset;
+            } else if rest.starts_with('>') || rest.starts_with(stops) || rest.is_empty() {
+                break;
+            } else {
+                self.offset += rest.chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        if text_start < self.offset {
+            nodes.push(GenexNode::Text(&self.input[text_start .. self.offset]));
+        }
+        Ok(nodes)
+    }
+
+    /// Parse the remainder of an expression after its opening `$<`, consuming the closing `>`.
+    fn parse_expr(&mut self) -> Result<GenexNode<'a>, self::Error> {
+        let name = self.parse_sequence(&[':'])?;
+        let args = if self.input[self.offset ..].starts_with(':') {
+            let mut args = vec![];
+            loop {
+                self.offset += 1;
+                args.push(self.parse_sequence(&[','])?);
+                if !self.input[self.offset ..].starts_with(',') {
+                    break;
+                }
+            }
+            Some(args)
+        } else {
+            None
+        };
+        ensure!(self.input[self.offset ..].starts_with('>'), GenexUnterminatedSnafu {
+            input: self.input
+        });
+        self.offset += 1;
+        Ok(GenexNode::Expr { name, args })
+    }
+}
+
+fn concat<'a>(lhs: Cow<'a, str>, rhs: Cow<'a, str>) -> Cow<'a, str> {
+    if lhs.is_empty() {
+        rhs
+    } else if rhs.is_empty() {
+        lhs
+    } else {
+        Cow::Owned(lhs.into_owned() + &rhs)
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, self::Error> {
+    match value {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        value => GenexConditionNotBooleanSnafu { value }.fail(),
+    }
+}
+
+/// `CMake`'s notion of a true constant, as used by `$<BOOL:...>`.
+fn is_truthy(value: &str) -> bool {
+    let upper = value.to_ascii_uppercase();
+    !(value.is_empty()
+        || value == "0"
+        || ["FALSE", "OFF", "N", "NO", "IGNORE", "NOTFOUND"].contains(&upper.as_str())
+        || upper.ends_with("-NOTFOUND"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(input: &str) -> Result<Cow<'_, str>, self::Error> {
+        GenexContext::new(ToolchainPlatform::X86_64_LINUX_GNU).evaluate(input)
+    }
+
+    #[test]
+    fn evaluate_plain() {
+        assert!(matches!(evaluate("LLVMSupport"), Ok(Cow::Borrowed("LLVMSupport"))));
+        assert!(matches!(evaluate("$<LINK_ONLY:ZLIB::ZLIB>"), Ok(Cow::Borrowed("ZLIB::ZLIB"))));
+        assert_eq!(evaluate("$<BUILD_INTERFACE:/src/include>").unwrap(), "");
+        assert_eq!(evaluate("$<INSTALL_INTERFACE:a,b>").unwrap(), "a,b");
+        assert_eq!(evaluate("$<CONFIG>").unwrap(), "Release");
+        assert_eq!(evaluate("$<PLATFORM_ID>").unwrap(), "Linux");
+        assert_eq!(evaluate("$<CXX_COMPILER_ID>").unwrap(), "GNU");
+    }
+
+    #[test]
+    fn evaluate_escaped() {
+        assert_eq!(evaluate("a$<COMMA>b").unwrap(), "a,b");
+        assert_eq!(evaluate("$<ANGLE-R>").unwrap(), ">");
+        assert_eq!(evaluate("$<1:$<ANGLE-R>$<SEMICOLON>>").unwrap(), ">;");
+        let context = GenexContext::new(ToolchainPlatform::X86_64_LINUX_GNU);
+        assert_eq!(context.evaluate_list_item("a$<SEMICOLON>b").unwrap(), ["a", "b"]);
+    }
+
+    #[test]
+    fn evaluate_nested() {
+        assert_eq!(evaluate("$<$<CONFIG:Release>:-lm>").unwrap(), "-lm");
+        assert_eq!(evaluate("$<$<CONFIG:Debug,RelWithDebInfo>:-lm>").unwrap(), "");
+        assert_eq!(evaluate("$<IF:$<PLATFORM_ID:Linux>,rt,ws2_32>").unwrap(), "rt");
+        assert_eq!(
+            evaluate("$<$<AND:$<CONFIG:release>,$<NOT:$<PLATFORM_ID:Windows>>>:pthread>").unwrap(),
+            "pthread"
+        );
+        assert_eq!(evaluate("$<$<OR:0,$<STREQUAL:$<CONFIG>,Release>>:x>").unwrap(), "x");
+        assert_eq!(evaluate("$<$<BOOL:x-NOTFOUND>:x>").unwrap(), "");
+        assert_eq!(evaluate("$<$<IN_LIST:b,a;b>:x>").unwrap(), "x");
+        assert_eq!(evaluate("$<$<EQUAL:1,01>:x>").unwrap(), "x");
+        assert_eq!(evaluate("$<$<CONFIG:$<IF:1,Release,Debug>>:x>").unwrap(), "x");
+    }
+
+    #[test]
+    fn evaluate_link_language() {
+        assert_eq!(evaluate("$<LINK_LANGUAGE>").unwrap(), "CXX");
+        assert_eq!(evaluate("$<$<LINK_LANGUAGE:CXX>:stdc++>").unwrap(), "stdc++");
+        assert_eq!(evaluate("$<$<LINK_LANGUAGE:Fortran>:gfortran>").unwrap(), "");
+    }
+
+    #[test]
+    fn evaluate_link_library() {
+        let context = GenexContext::new(ToolchainPlatform::X86_64_LINUX_GNU);
+        let items = context.evaluate_list_item("$<LINK_LIBRARY:WHOLE_ARCHIVE,a,b>").unwrap();
+        assert_eq!(items, ["a", "b"]);
+    }
+
+    #[test]
+    fn evaluate_malformed() {
+        assert!(matches!(evaluate("$<CONFIG"), Err(self::Error::GenexUnterminated { .. })));
+        assert!(matches!(evaluate("$<NOT:1,0>"), Err(self::Error::GenexArgumentCount { .. })));
+        assert!(matches!(evaluate("$<IF:$<CONFIG>,a,b>"), Err(self::Error::GenexConditionNotBoolean { .. })));
+    }
+
+    #[test]
+    fn evaluate_unknown() {
+        let Err(self::Error::GenexUnknownExpression { name }) = evaluate("$<TARGET_FILE:LLVMSupport>") else {
+            panic!("expected `TARGET_FILE` to be unknown");
+        };
+        assert_eq!(name, "TARGET_FILE");
+
+        // NOTE: Items using unknown expressions are dropped from lists, wherever the expression is nested.
+        let context = GenexContext::new(ToolchainPlatform::X86_64_LINUX_GNU);
+        for input in [
+            "$<$<COMPILE_LANGUAGE:CXX>:-fno-rtti>",
+            "$<TARGET_PROPERTY:LLVMSupport,INTERFACE_LINK_LIBRARIES>",
+            "$<1:a$<TARGET_FILE:LLVMSupport>>",
+        ] {
+            assert!(context.evaluate_list_item(input).unwrap().is_empty());
+        }
+    }
+}
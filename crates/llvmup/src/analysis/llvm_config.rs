@@ -0,0 +1,429 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use indexmap::IndexSet;
+use snafu::prelude::*;
+use std::{cell::OnceCell, collections::BTreeSet};
+
+use crate::{
+    analysis::{
+        backends::ToolchainTargetBackendLibrary,
+        closure::ToolchainLinkClosure,
+        cxx_standard_flag,
+        ToolchainComponentDependencyNode,
+        ToolchainComponentDependencyNodeKind,
+    },
+    toolchain::platform::{ToolchainArch, ToolchainSys},
+    ToolchainAnalysis,
+    ToolchainComponent,
+    ToolchainContext,
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    ComponentNotFound { name: String },
+    LinkSharedLibraryNotFound,
+    LlvmupAnalysisClosure { source: crate::analysis::closure::Error },
+    LlvmupAnalysisGenex { source: crate::analysis::genex::Error },
+    OptionUnknown { option: String },
+}
+
+impl From<self::Error> for crate::Error {
+    fn from(source: self::Error) -> Self {
+        Self::LlvmupAnalysisLlvmConfig { source }
+    }
+}
+
+/// The name of the monolithic shared library LLVM optionally builds.
+const LLVM_SHARED_LIBRARY: &str = "LLVM";
+
+/// Answers the queries of `llvm-config` (e.g., `--libs core x86`) for an analysed toolchain, in the same format.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct ToolchainLlvmConfig<'a> {
+    analysis: &'a ToolchainAnalysis<'a>,
+    context: ToolchainContext,
+    root: Utf8PathBuf,
+}
+
+impl<'a> ToolchainLlvmConfig<'a> {
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn new(analysis: &'a ToolchainAnalysis<'a>, context: ToolchainContext, root: Utf8PathBuf) -> Self {
+        Self {
+            analysis,
+            context,
+            root,
+        }
+    }
+
+    /// The LLVM libraries, in graph order.
+    fn llvm_libraries(&self) -> impl Iterator<Item = ToolchainComponentDependencyNode<'a>> + '_ {
+        self.analysis.dependencies.nodes().filter(|node| {
+            node.component == Some(ToolchainComponent::Llvm)
+                && node.name.len() > "LLVM".len()
+                && node.name.starts_with("LLVM")
+                && node.kind != ToolchainComponentDependencyNodeKind::Interface
+        })
+    }
+
+    /// The names `llvm-config` knows components by (e.g., `core` for `LLVMCore`), including pseudo-components.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn components(&self) -> BTreeSet<String> {
+        let mut components = self
+            .llvm_libraries()
+            .map(|node| node.name["LLVM".len()..].to_ascii_lowercase())
+            .collect::<BTreeSet<_>>();
+        components.extend(
+            self.analysis
+                .target_backends()
+                .keys()
+                .map(|backend| backend.to_ascii_lowercase()),
+        );
+        components.extend(["all", "all-targets", "engine", "native", "nativecodegen"].map(String::from));
+        components
+    }
+
+    /// The backend of the host platform (e.g., `X86`), if built.
+    fn native_backend(&self) -> Option<&'static str> {
+        let backend = match self.context.platform.arch() {
+            ToolchainArch::Aarch64 | ToolchainArch::Arm64 => "AArch64",
+            ToolchainArch::Arm => "ARM",
+            ToolchainArch::I686 | ToolchainArch::X86_64 => "X86",
+            ToolchainArch::PowerPc64Le => "PowerPC",
+            ToolchainArch::RiscV64 => "RISCV",
+            ToolchainArch::S390X => "SystemZ",
+        };
+        self.analysis.target_backends().contains_key(backend).then_some(backend)
+    }
+
+    /// Resolve `llvm-config` component names to the LLVM libraries they denote (all of them if there are none).
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn component_libraries(&self, components: &[&str]) -> Result<Vec<&'a str>, self::Error> {
+        if components.is_empty() {
+            return self.component_libraries(&["all"]);
+        }
+        let backends = self.analysis.target_backends();
+        let backend_libraries = |backend: &str, libraries: &[ToolchainTargetBackendLibrary]| {
+            backends
+                .get(backend)
+                .into_iter()
+                .flat_map(|backend| backend.iter())
+                .filter(|(library, _)| libraries.is_empty() || libraries.contains(library))
+                .map(|(_, name)| *name)
+                .collect::<Vec<_>>()
+        };
+        let mut libraries = IndexSet::new();
+        for component in components {
+            let component = component.to_ascii_lowercase();
+            match component.as_str() {
+                "all" => libraries.extend(self.llvm_libraries().map(|node| node.name)),
+                "all-targets" => libraries.extend(backends.values().flat_map(|backend| backend.values().copied())),
+                "engine" | "native" | "nativecodegen" => {
+                    let Some(native) = self.native_backend() else {
+                        continue;
+                    };
+                    let only = if component == "nativecodegen" {
+                        &[
+                            ToolchainTargetBackendLibrary::CodeGen,
+                            ToolchainTargetBackendLibrary::Desc,
+                            ToolchainTargetBackendLibrary::Info,
+                        ][..]
+                    } else {
+                        &[]
+                    };
+                    libraries.extend(backend_libraries(native, only));
+                    if component == "engine" {
+                        // NOTE: Like `llvm-config`, prefer MCJIT, and fall back to the interpreter.
+                        let engine = ["LLVMMCJIT", "LLVMInterpreter"]
+                            .into_iter()
+                            .find_map(|engine| self.llvm_libraries().find(|node| node.name == engine));
+                        libraries.extend(engine.map(|node| node.name));
+                    }
+                },
+                _ => {
+                    if let Some(backend) = backends.keys().find(|backend| backend.eq_ignore_ascii_case(&component)) {
+                        libraries.extend(backend_libraries(backend, &[]));
+                        continue;
+                    }
+                    let node = self
+                        .llvm_libraries()
+                        .find(|node| node.name["LLVM".len()..].eq_ignore_ascii_case(&component))
+                        .context(ComponentNotFoundSnafu { name: component })?;
+                    libraries.insert(node.name);
+                },
+            }
+        }
+        Ok(libraries.into_iter().collect())
+    }
+
+    /// The closure of the libraries denoted by `components`.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn link_closure(&self, components: &[&str]) -> Result<ToolchainLinkClosure<'a>, self::Error> {
+        let libraries = self.component_libraries(components)?;
+        ToolchainLinkClosure::dependencies(&self.analysis.dependencies, libraries).context(LlvmupAnalysisClosureSnafu)
+    }
+
+    /// The monolithic LLVM shared library, if the toolchain provides one.
+    fn shared_library(&self) -> Option<ToolchainComponentDependencyNode<'a>> {
+        let target = self
+            .analysis
+            .components_targets
+            .get(&ToolchainComponent::Llvm)?
+            .get(LLVM_SHARED_LIBRARY)?;
+        Some(ToolchainComponentDependencyNode::new(
+            ToolchainComponent::Llvm,
+            LLVM_SHARED_LIBRARY,
+            target,
+            self.analysis.genex_context.config,
+        ))
+    }
+
+    /// The closure of the libraries denoted by `components`, computed on first use.
+    fn cached_link_closure<'c>(
+        &self,
+        closure: &'c OnceCell<ToolchainLinkClosure<'a>>,
+        components: &[&str],
+    ) -> Result<&'c ToolchainLinkClosure<'a>, self::Error> {
+        if let Some(closure) = closure.get() {
+            return Ok(closure);
+        }
+        let computed = self.link_closure(components)?;
+        Ok(closure.get_or_init(|| computed))
+    }
+
+    /// The LLVM libraries to link for `components`, in link order (or the monolithic shared library if `shared`).
+    fn libraries(
+        &self,
+        closure: &ToolchainLinkClosure<'a>,
+        shared: bool,
+    ) -> Result<Vec<ToolchainComponentDependencyNode<'a>>, self::Error> {
+        if shared {
+            let node = self.shared_library().context(LinkSharedLibraryNotFoundSnafu)?;
+            return Ok(vec![node]);
+        }
+        Ok(closure
+            .libraries()
+            .into_iter()
+            .filter(|node| node.component.is_some() && node.name.starts_with("LLVM"))
+            .collect())
+    }
+
+    /// The libraries to link that aren't LLVM's (e.g., `m` or `ZLIB::ZLIB`), in link order.
+    fn system_libraries(closure: &ToolchainLinkClosure<'a>) -> Vec<ToolchainComponentDependencyNode<'a>> {
+        closure
+            .libraries()
+            .into_iter()
+            .filter(|node| node.component.is_none() || !node.name.starts_with("LLVM"))
+            .collect()
+    }
+
+    fn render_library(&self, library: &ToolchainComponentDependencyNode<'_>) -> String {
+        let windows = self.context.platform.sys() == ToolchainSys::Windows;
+        match library.link_location(self.context.platform).map(Utf8Path::new) {
+            Some(location) if windows => location.file_name().unwrap_or(library.name).to_owned(),
+            Some(location) => {
+                let stem = location.file_stem().unwrap_or(library.name);
+                format!("-l{}", stem.strip_prefix("lib").unwrap_or(stem))
+            },
+            // NOTE: Linker flags (e.g., `-lpthread`) and paths are passed through as they are.
+            None if library.name.starts_with('-') || library.name.contains(['/', '\\']) => library.name.to_owned(),
+            None if windows => format!("{}.lib", library.name),
+            None => format!("-l{}", library.name),
+        }
+    }
+
+    fn cppflags(&self, closure: &ToolchainLinkClosure<'a>) -> Result<Vec<String>, self::Error> {
+        let mut flags = IndexSet::new();
+        flags.insert(format!("-I{}", self.root.join("include")));
+        for dir in closure
+            .include_directories()
+            .into_iter()
+            .chain(closure.system_include_directories())
+        {
+            flags.insert(format!("-I{}", self.root.join(dir)));
+        }
+        for node in &closure.nodes {
+            for definition in node.interface_compile_definitions.iter() {
+                for definition in self
+                    .analysis
+                    .genex_context
+                    .evaluate_list_item(definition)
+                    .context(LlvmupAnalysisGenexSnafu)?
+                {
+                    flags.insert(format!("-D{definition}"));
+                }
+            }
+        }
+        Ok(flags.into_iter().collect())
+    }
+
+    fn cxxflags(&self, closure: &ToolchainLinkClosure<'a>) -> Result<Vec<String>, self::Error> {
+        let mut flags = self.cppflags(closure)?.into_iter().collect::<IndexSet<_>>();
+        // NOTE: Only the highest standard any target requires is selected, as with `CMake`.
+        let standard = closure
+            .nodes
+            .iter()
+            .flat_map(|node| node.interface_compile_features.iter())
+            .filter_map(|feature| feature.strip_prefix("cxx_std_")?.parse().ok())
+            .max();
+        if let Some(standard) = standard {
+            flags.insert(cxx_standard_flag(self.context.platform.sys(), standard));
+        }
+        for node in &closure.nodes {
+            for option in node.interface_compile_options.iter() {
+                for option in self
+                    .analysis
+                    .genex_context
+                    .evaluate_list_item(option)
+                    .context(LlvmupAnalysisGenexSnafu)?
+                {
+                    flags.insert(option.into_owned());
+                }
+            }
+        }
+        Ok(flags.into_iter().collect())
+    }
+
+    fn ldflags(&self, closure: &ToolchainLinkClosure<'a>) -> Vec<String> {
+        let flag = if self.context.platform.sys() == ToolchainSys::Windows {
+            "-LIBPATH:"
+        } else {
+            "-L"
+        };
+        let mut flags = IndexSet::new();
+        flags.insert(format!("{flag}{}", self.root.join("lib")));
+        for node in &closure.nodes {
+            for dir in node.interface_link_directories.iter() {
+                flags.insert(format!("{flag}{}", self.root.join(dir)));
+            }
+        }
+        flags.into_iter().collect()
+    }
+
+    /// Answer an `llvm-config` invocation, given its arguments (options and component names, in any order).
+    ///
+    /// As with `llvm-config`, each option's answer is printed on its own line, with the library options answered
+    /// last.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn query(&self, args: &[&str]) -> Result<String, self::Error> {
+        let (options, components): (Vec<&str>, Vec<&str>) = args.iter().partition(|arg| arg.starts_with('-'));
+        let shared = options.contains(&"--link-shared");
+        // NOTE: The closure is only computed for the options which need it, so that e.g. `--version` is answered even
+        // when a component is unknown.
+        let closure = OnceCell::new();
+        let closure = || self.cached_link_closure(&closure, &components);
+
+        let mut lines = Vec::<String>::new();
+        let mut library_lines = Vec::<String>::new();
+        let mut system_lines = Vec::<String>::new();
+        let join = |items: Vec<String>| items.join(" ");
+        for option in options {
+            match option {
+                "--version" => lines.push(self.context.release.to_string()),
+                "--prefix" => lines.push(self.root.to_string()),
+                "--bindir" => lines.push(self.root.join("bin").to_string()),
+                "--includedir" => lines.push(self.root.join("include").to_string()),
+                "--libdir" => lines.push(self.root.join("lib").to_string()),
+                "--cmakedir" => lines.push(self.root.join("lib").join("cmake").join("llvm").to_string()),
+                "--cppflags" | "--cflags" => lines.push(join(self.cppflags(closure()?)?)),
+                "--cxxflags" => lines.push(join(self.cxxflags(closure()?)?)),
+                "--ldflags" => lines.push(join(self.ldflags(closure()?))),
+                "--components" => lines.push(join(self.components().into_iter().collect())),
+                "--targets-built" => lines.push(
+                    self.analysis
+                        .target_backends()
+                        .keys()
+                        .copied()
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                ),
+                "--host-target" => lines.push(String::from(self.context.platform.llvm_target_triple())),
+                "--build-mode" => lines.push(self.analysis.genex_context.config.to_owned()),
+                "--shared-mode" => lines.push(String::from(if self.shared_library().is_some() {
+                    "shared"
+                } else {
+                    "static"
+                })),
+                "--libs" => library_lines.push(join(
+                    self.libraries(closure()?, shared)?
+                        .iter()
+                        .map(|library| self.render_library(library))
+                        .collect(),
+                )),
+                "--libnames" => library_lines.push(join(
+                    self.libraries(closure()?, shared)?
+                        .iter()
+                        .filter_map(|library| Utf8Path::new(library.link_location(self.context.platform)?).file_name())
+                        .map(String::from)
+                        .collect(),
+                )),
+                "--libfiles" => library_lines.push(join(
+                    self.libraries(closure()?, shared)?
+                        .iter()
+                        .filter_map(|library| library.link_location(self.context.platform))
+                        .map(|location| self.root.join(location).to_string())
+                        .collect(),
+                )),
+                "--system-libs" => system_lines.push(join(
+                    Self::system_libraries(closure()?)
+                        .iter()
+                        .map(|library| self.render_library(library))
+                        .collect(),
+                )),
+                "--link-shared" | "--link-static" => {},
+                option => return OptionUnknownSnafu { option }.fail(),
+            }
+        }
+        lines.extend(library_lines);
+        lines.extend(system_lines);
+
+        let mut output = lines.join("\n");
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{analysis, context, manifests, target},
+        ToolchainPlatform,
+    };
+
+    /// Answer `args` for a toolchain with `LLVMSupport` (depending on `LLVMDemangle`), and the LLVM shared library if
+    /// `shared`.
+    fn query(args: &[&str], shared: bool) -> Result<String, self::Error> {
+        let mut targets = vec![
+            target("LLVMSupport", "STATIC_LIBRARY", r#""LLVMDemangle""#, "lib/libLLVMSupport.a", ""),
+            target("LLVMDemangle", "STATIC_LIBRARY", "", "lib/libLLVMDemangle.a", ""),
+        ];
+        if shared {
+            targets.push(target("LLVM", "SHARED_LIBRARY", "", "lib/libLLVM.so", ""));
+        }
+        let platform = ToolchainPlatform::X86_64_LINUX_GNU;
+        let analysis = analysis(platform, manifests(&targets));
+        ToolchainLlvmConfig::new(analysis.get(), context(platform), Utf8PathBuf::from("/llvmup")).query(args)
+    }
+
+    #[test]
+    fn query_without_closure() {
+        assert_eq!(query(&["--version", "--prefix", "unknown"], false).unwrap(), "17.0.6\n/llvmup\n");
+        let Err(error) = query(&["--libs", "unknown"], false) else {
+            panic!("expected an unknown component to fail to link");
+        };
+        assert!(matches!(error, self::Error::ComponentNotFound { .. }));
+    }
+
+    #[test]
+    fn query_libs() {
+        assert_eq!(query(&["--libs", "support"], false).unwrap(), "-lLLVMSupport -lLLVMDemangle\n");
+    }
+
+    #[test]
+    fn query_shared_mode() {
+        assert_eq!(query(&["--shared-mode"], false).unwrap(), "static\n");
+        assert_eq!(query(&["--shared-mode"], true).unwrap(), "shared\n");
+    }
+}
@@ -0,0 +1,233 @@
+use camino::Utf8Path;
+use snafu::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    generation::cargo::{cfg_target_arch, cfg_target_env, cfg_target_os},
+    GenexContext,
+    Llvmup,
+    Toolchain,
+    ToolchainConfig,
+    ToolchainPlatform,
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    CargoTargetPlatformUnsupported {
+        os: String,
+        env: String,
+        arch: String,
+    },
+    LlvmupConfigNotFound,
+    LlvmupConfigUnknown {
+        config: String,
+    },
+    StdEnvVar {
+        name: &'static str,
+        source: std::env::VarError,
+    },
+    TokioRuntimeBuild {
+        source: std::io::Error,
+    },
+}
+
+impl From<self::Error> for crate::Error {
+    fn from(source: self::Error) -> Self {
+        crate::Error::LlvmupBuild { source }
+    }
+}
+
+/// The build configurations `LLVMUP_CONFIG` may select.
+const LLVMUP_CONFIGS: [&str; 4] = ["Debug", "MinSizeRel", "Release", "RelWithDebInfo"];
+
+/// Link the toolchain libraries enabled through the crate's features, for the toolchain described by the
+/// `llvmup.toml` found upward from `CARGO_MANIFEST_DIR`, installed under `LLVMUP_HOME` (or `~/.llvmup`).
+#[cfg_attr(feature = "tracing", tracing::instrument)]
+pub fn link() -> Result<(), crate::Error> {
+    LlvmupBuild::default().link()
+}
+
+/// Resolves a toolchain when the build script runs (rather than when `build_llvmup.rs` is generated), so that
+/// nothing about the machine the crate was generated on is baked into it.
+#[derive(Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct LlvmupBuild<'a> {
+    root: Option<&'a Utf8Path>,
+    toolchain: Option<Toolchain>,
+}
+
+impl<'a> LlvmupBuild<'a> {
+    /// Look for toolchains under `root` instead of `LLVMUP_HOME` (or `~/.llvmup`).
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn root(mut self, root: &'a Utf8Path) -> Self {
+        self.root = Some(root);
+        self
+    }
+
+    /// Link against `toolchain` instead of the one described by `llvmup.toml`.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn toolchain(mut self, toolchain: Toolchain) -> Self {
+        self.toolchain = Some(toolchain);
+        self
+    }
+
+    /// Like [`LlvmupBuild::link_async`], but on a runtime of its own, for build scripts which aren't async.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn link(self) -> Result<(), crate::Error> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context(TokioRuntimeBuildSnafu)?;
+        runtime.block_on(self.link_async())
+    }
+
+    /// Print the `cargo:rustc-link-*` instructions for the libraries whose `CARGO_FEATURE_*` variables are set.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn link_async(self) -> Result<(), crate::Error> {
+        println!("cargo:rerun-if-env-changed=LLVMUP_HOME");
+        println!("cargo:rerun-if-env-changed=LLVMUP_CONFIG");
+
+        let home = env_var("LLVMUP_HOME")?;
+        let root = self.root.or(home.as_deref().map(Utf8Path::new));
+        let mut builder = Llvmup::builder();
+        if let Some(root) = root {
+            builder = builder.root(root);
+        }
+        let mut llvmup = builder.build()?;
+
+        let toolchain = if let Some(toolchain) = self.toolchain {
+            toolchain
+        } else {
+            let (path, config) = ToolchainConfig::discover()
+                .await?
+                .context(LlvmupConfigNotFoundSnafu)?;
+            println!("cargo:rerun-if-changed={path}");
+            config.toolchain(&llvmup.directories, target_platform()?).await?
+        };
+
+        let context = toolchain.context;
+        let components = toolchain.components.clone();
+        let handle = llvmup.register_toolchain(toolchain);
+        let manifests = llvmup.load_toolchain_manifests(handle, &components).await?;
+
+        // NOTE: Reinstalling the toolchain (or refetching its manifests) may change what is linked.
+        let root = llvmup.directories.toolchain_root_path(context);
+        if root.exists() {
+            println!("cargo:rerun-if-changed={root}");
+        }
+        for component in &components {
+            let installed = llvmup.directories.manifest_path(context, *component);
+            let cached = llvmup.directories.manifest_cache_path(context, *component);
+            if let Some(path) = [Some(installed), cached].into_iter().flatten().find(|path| path.exists()) {
+                println!("cargo:rerun-if-changed={path}");
+            }
+        }
+
+        // NOTE: Like `build_llvmup.rs`, the configuration is picked from Cargo's profile among those available, unless
+        // overridden through `LLVMUP_CONFIG`.
+        let config = match env_var("LLVMUP_CONFIG")? {
+            Some(config) => LLVMUP_CONFIGS
+                .into_iter()
+                .find(|known| *known == config)
+                .context(LlvmupConfigUnknownSnafu { config })?,
+            None => select_config(env_var("PROFILE")?.as_deref(), &manifests.imported_configurations()),
+        };
+
+        let genex_context = GenexContext::new(context.platform).config(config);
+        let analysis = llvmup
+            .cached_analysis_with_genex_context(handle, genex_context, &components)
+            .await?;
+        let generator = llvmup.generator(context, analysis.get(), BTreeSet::new(), BTreeMap::new())?;
+        let cargo_config = generator.generate_cargo_config()?;
+
+        for dir in cargo_config.build_link_dirs.values().flatten() {
+            println!("cargo:rustc-link-search=native={}", root.join(dir));
+        }
+        for item in &cargo_config.build_link_items {
+            if item.enabled(cargo_feature_enabled) {
+                for directive in &item.directives {
+                    println!("{directive}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The configuration to link for Cargo's `profile`, among those the toolchain's targets were imported in, preferred
+/// as in `build_llvmup.rs`: `Release`, `MinSizeRel` or `RelWithDebInfo` for release builds, and `Debug` or
+/// `RelWithDebInfo` otherwise.
+fn select_config(profile: Option<&str>, imported_configurations: &BTreeSet<&str>) -> &'static str {
+    let available = LLVMUP_CONFIGS
+        .into_iter()
+        .filter(|config| {
+            imported_configurations
+                .iter()
+                .any(|imported| imported.eq_ignore_ascii_case(config))
+        })
+        .collect::<BTreeSet<_>>();
+    let preferred: &[&str] = match profile {
+        Some("release") => &["Release", "MinSizeRel", "RelWithDebInfo"],
+        _ => &["Debug", "RelWithDebInfo"],
+    };
+    preferred
+        .iter()
+        .find(|config| available.contains(*config))
+        .or_else(|| available.first())
+        .copied()
+        .unwrap_or("Release")
+}
+
+fn env_var(name: &'static str) -> Result<Option<String>, self::Error> {
+    match std::env::var(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(std::env::VarError::NotPresent) => Ok(None),
+        Err(source) => Err(self::Error::StdEnvVar { name, source }),
+    }
+}
+
+/// The platform Cargo is building for, which (unlike [`ToolchainPlatform::detect`]) differs from the build script's
+/// own when cross-compiling.
+fn target_platform() -> Result<ToolchainPlatform, self::Error> {
+    let os = env_var("CARGO_CFG_TARGET_OS")?.unwrap_or_default();
+    let env = env_var("CARGO_CFG_TARGET_ENV")?.unwrap_or_default();
+    let arch = env_var("CARGO_CFG_TARGET_ARCH")?.unwrap_or_default();
+    ToolchainPlatform::ALL
+        .into_iter()
+        .find(|platform| {
+            cfg_target_os(platform.sys()) == os
+                && cfg_target_env(platform.sys()).unwrap_or_default() == env
+                && cfg_target_arch(platform.arch()) == arch
+        })
+        .context(CargoTargetPlatformUnsupportedSnafu { os, env, arch })
+}
+
+/// Whether Cargo enabled `feature` for the crate being built (e.g., `CARGO_FEATURE_LLVMCORE` for `LLVMCore`).
+fn cargo_feature_enabled(feature: &str) -> bool {
+    let name = feature.to_ascii_uppercase().replace('-', "_");
+    std::env::var_os(format!("CARGO_FEATURE_{name}")).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_config_from_profile() {
+        let all = BTreeSet::from(["DEBUG", "MINSIZEREL", "RELEASE", "RELWITHDEBINFO"]);
+        assert_eq!(select_config(Some("release"), &all), "Release");
+        assert_eq!(select_config(Some("debug"), &all), "Debug");
+        assert_eq!(select_config(None, &all), "Debug");
+
+        let optimized = BTreeSet::from(["MINSIZEREL", "RELWITHDEBINFO"]);
+        assert_eq!(select_config(Some("release"), &optimized), "MinSizeRel");
+        assert_eq!(select_config(Some("debug"), &optimized), "RelWithDebInfo");
+
+        let release = BTreeSet::from(["RELEASE"]);
+        assert_eq!(select_config(Some("debug"), &release), "Release");
+        assert_eq!(select_config(Some("release"), &BTreeSet::new()), "Release");
+    }
+}
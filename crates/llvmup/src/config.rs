@@ -0,0 +1,398 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+use snafu::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    config::releases::{parse_release_name, ToolchainAvailableRelease},
+    toolchain::release::ToolchainReleaseConstraint,
+    Toolchain,
+    ToolchainComponent,
+    ToolchainContext,
+    ToolchainPlatform,
+    ToolchainRelease,
+    ToolchainRevision,
+    ToolchainVariant,
+};
+
+pub mod lockfile;
+pub mod releases;
+
+pub const TOOLCHAIN_CONFIG_FILE_NAME: &str = "llvmup.toml";
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    CaminoUtf8PathBufTryFrom {
+        source: camino::FromPathBufError,
+    },
+    LlvmupConfigReleases {
+        source: crate::config::releases::Error,
+    },
+    LlvmupToolchainNew {
+        source: crate::toolchain::Error,
+    },
+    StdEnvCurrentDir {
+        source: std::io::Error,
+    },
+    TokioFsReadDir {
+        source: tokio::io::Error,
+    },
+    TokioFsReadToString {
+        source: tokio::io::Error,
+    },
+    TokioFsTryExists {
+        source: tokio::io::Error,
+    },
+    TomlFromStr {
+        path: Utf8PathBuf,
+        source: toml::de::Error,
+    },
+    ToolchainConfigMoldReleaseMissing {
+        platform: ToolchainPlatform,
+    },
+    ToolchainConfigReleaseNotInstalled {
+        variant: ToolchainVariant,
+        release: ToolchainReleaseConstraint,
+        platform: ToolchainPlatform,
+    },
+    ToolchainConfigReleaseUnsatisfied {
+        variant: ToolchainVariant,
+        release: ToolchainReleaseConstraint,
+        platform: ToolchainPlatform,
+    },
+}
+
+impl From<self::Error> for crate::Error {
+    fn from(source: self::Error) -> Self {
+        crate::Error::LlvmupConfig { source }
+    }
+}
+
+/// The contents of a project-level `llvmup.toml` file.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ToolchainConfig {
+    pub toolchain: ToolchainConfigToolchain,
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ToolchainConfigToolchain {
+    pub variant: ToolchainVariant,
+    pub release: ToolchainReleaseConstraint,
+    #[serde(default)]
+    pub revision: Option<usize>,
+    pub components: BTreeSet<ToolchainConfigComponent>,
+    #[serde(default)]
+    pub mold: Option<ToolchainRelease>,
+    #[serde(default)]
+    pub targets: BTreeMap<ToolchainPlatform, ToolchainConfigOverride>,
+}
+
+/// Per-platform overrides applied on top of the `[toolchain]` table.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ToolchainConfigOverride {
+    #[serde(default)]
+    pub release: Option<ToolchainReleaseConstraint>,
+    #[serde(default)]
+    pub revision: Option<usize>,
+    #[serde(default)]
+    pub components: Option<BTreeSet<ToolchainConfigComponent>>,
+    #[serde(default)]
+    pub mold: Option<ToolchainRelease>,
+}
+
+/// A component named in `llvmup.toml`. `tool_mold` is kept apart since its release and platform are only known
+/// once the configuration is resolved for a particular platform.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
+pub enum ToolchainConfigComponent {
+    Component(ToolchainComponent),
+    ToolMold,
+}
+
+impl<'de> Deserialize<'de> for ToolchainConfigComponent {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        if value == "tool_mold" {
+            return Ok(Self::ToolMold);
+        }
+        value.parse().map(Self::Component).map_err(serde::de::Error::custom)
+    }
+}
+
+impl ToolchainConfig {
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn from_toml(path: &Utf8Path, text: &str) -> Result<Self, self::Error> {
+        toml::from_str(text).context(TomlFromStrSnafu { path })
+    }
+
+    /// The platforms the configuration is locked for: the `host` and any with a `[toolchain.targets]` override.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn platforms(&self, host: ToolchainPlatform) -> BTreeSet<ToolchainPlatform> {
+        let mut platforms = self.toolchain.targets.keys().copied().collect::<BTreeSet<_>>();
+        platforms.insert(host);
+        platforms
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn load(path: &Utf8Path) -> Result<Self, self::Error> {
+        let text = tokio::fs::read_to_string(path)
+            .await
+            .context(TokioFsReadToStringSnafu)?;
+        Self::from_toml(path, &text)
+    }
+
+    /// Search for `llvmup.toml` upward from `CARGO_MANIFEST_DIR` (when run from a build script) or else from the
+    /// current directory.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn discover() -> Result<Option<(Utf8PathBuf, Self)>, self::Error> {
+        let start = if let Some(dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
+            std::path::PathBuf::from(dir)
+        } else {
+            std::env::current_dir().context(StdEnvCurrentDirSnafu)?
+        };
+        let start = Utf8PathBuf::try_from(start).context(CaminoUtf8PathBufTryFromSnafu)?;
+        Self::discover_from(&start).await
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn discover_from(start: &Utf8Path) -> Result<Option<(Utf8PathBuf, Self)>, self::Error> {
+        for dir in start.ancestors() {
+            let path = dir.join(TOOLCHAIN_CONFIG_FILE_NAME);
+            if tokio::fs::try_exists(&path).await.context(TokioFsTryExistsSnafu)? {
+                let config = Self::load(&path).await?;
+                return Ok(Some((path, config)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Resolve the toolchain for `platform`, picking the greatest release installed under `directories` for a range of
+    /// releases. Installing (or locking) a release a range doesn't yet cover requires
+    /// [`ToolchainConfig::toolchain_from_releases`] instead.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn toolchain(
+        &self,
+        directories: &crate::Directories,
+        platform: ToolchainPlatform,
+    ) -> Result<Toolchain, self::Error> {
+        let selection = self.select(platform);
+        let (release, revision) = match selection.release {
+            ToolchainReleaseConstraint::Exact(release) => (*release, ToolchainRevision::new(selection.revision)),
+            constraint @ ToolchainReleaseConstraint::Range(_) => {
+                resolve_installed_release(directories, selection.variant, constraint, selection.revision, platform)
+                    .await?
+                    .context(ToolchainConfigReleaseNotInstalledSnafu {
+                        variant: selection.variant,
+                        release: constraint.clone(),
+                        platform,
+                    })?
+            },
+        };
+        selection.toolchain(platform, release, revision)
+    }
+
+    /// Resolve the toolchain for `platform`, picking the greatest of the published `releases` (see
+    /// [`releases::fetch_available_releases`]) with assets for `platform` for a range of releases.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn toolchain_from_releases(
+        &self,
+        platform: ToolchainPlatform,
+        releases: &[ToolchainAvailableRelease],
+    ) -> Result<Toolchain, self::Error> {
+        let selection = self.select(platform);
+        let (release, revision) = match selection.release {
+            ToolchainReleaseConstraint::Exact(release) => (*release, ToolchainRevision::new(selection.revision)),
+            constraint @ ToolchainReleaseConstraint::Range(_) => releases
+                .iter()
+                .filter(|available| {
+                    constraint.matches(&available.release)
+                        && available.platforms.contains(&platform)
+                        && selection
+                            .revision
+                            .is_none_or(|revision| available.revision.get() == Some(revision))
+                })
+                .map(|available| (available.release, available.revision.get()))
+                .max()
+                .map(|(release, revision)| (release, ToolchainRevision::new(revision)))
+                .context(ToolchainConfigReleaseUnsatisfiedSnafu {
+                    variant: selection.variant,
+                    release: constraint.clone(),
+                    platform,
+                })?,
+        };
+        selection.toolchain(platform, release, revision)
+    }
+
+    /// The settings of the `[toolchain]` table with the overrides for `platform` applied.
+    fn select(&self, platform: ToolchainPlatform) -> ToolchainConfigSelection<'_> {
+        let ToolchainConfigToolchain {
+            variant,
+            release,
+            revision,
+            components,
+            mold,
+            targets,
+        } = &self.toolchain;
+        match targets.get(&platform) {
+            Some(overrides) => ToolchainConfigSelection {
+                variant: *variant,
+                release: overrides.release.as_ref().unwrap_or(release),
+                revision: overrides.revision.or(*revision),
+                components: overrides.components.as_ref().unwrap_or(components),
+                mold: overrides.mold.or(*mold),
+            },
+            None => ToolchainConfigSelection {
+                variant: *variant,
+                release,
+                revision: *revision,
+                components,
+                mold: *mold,
+            },
+        }
+    }
+}
+
+/// The settings of a [`ToolchainConfig`] which apply to a particular platform.
+struct ToolchainConfigSelection<'a> {
+    variant: ToolchainVariant,
+    release: &'a ToolchainReleaseConstraint,
+    revision: Option<usize>,
+    components: &'a BTreeSet<ToolchainConfigComponent>,
+    mold: Option<ToolchainRelease>,
+}
+
+impl ToolchainConfigSelection<'_> {
+    fn toolchain(
+        &self,
+        platform: ToolchainPlatform,
+        release: ToolchainRelease,
+        revision: ToolchainRevision,
+    ) -> Result<Toolchain, self::Error> {
+        let context = ToolchainContext::new(self.variant, release, revision, platform);
+        let components = self
+            .components
+            .iter()
+            .map(|component| match component {
+                ToolchainConfigComponent::Component(component) => Ok(*component),
+                ToolchainConfigComponent::ToolMold => {
+                    let release = self.mold.context(ToolchainConfigMoldReleaseMissingSnafu { platform })?;
+                    Ok(ToolchainComponent::ToolMold { platform, release })
+                },
+            })
+            .collect::<Result<Vec<_>, self::Error>>()?;
+
+        Toolchain::new(context, &components).context(LlvmupToolchainNewSnafu)
+    }
+}
+
+/// Find the greatest installed release (and revision) of `variant` for `platform` satisfying `constraint`.
+#[cfg_attr(feature = "tracing", tracing::instrument)]
+async fn resolve_installed_release(
+    directories: &crate::Directories,
+    variant: ToolchainVariant,
+    constraint: &ToolchainReleaseConstraint,
+    revision: Option<usize>,
+    platform: ToolchainPlatform,
+) -> Result<Option<(ToolchainRelease, ToolchainRevision)>, self::Error> {
+    let trees = directories.trees();
+    if !tokio::fs::try_exists(trees).await.context(TokioFsTryExistsSnafu)? {
+        return Ok(None);
+    }
+
+    let mut best = None::<(ToolchainRelease, Option<usize>)>;
+    let mut entries = tokio::fs::read_dir(trees).await.context(TokioFsReadDirSnafu)?;
+    while let Some(entry) = entries.next_entry().await.context(TokioFsReadDirSnafu)? {
+        let Some((tree_release, tree_revision)) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| parse_release_name(variant, name))
+        else {
+            continue;
+        };
+        if !constraint.matches(&tree_release) || revision.is_some_and(|revision| tree_revision.get() != Some(revision))
+        {
+            continue;
+        }
+        let platform_dir = entry.path().join(platform.to_string());
+        if !tokio::fs::try_exists(platform_dir)
+            .await
+            .context(TokioFsTryExistsSnafu)?
+        {
+            continue;
+        }
+        let candidate = (tree_release, tree_revision.get());
+        if best.is_none_or(|best| candidate > best) {
+            best = Some(candidate);
+        }
+    }
+
+    Ok(best.map(|(release, revision)| (release, ToolchainRevision::new(revision))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn available(release: &str, revision: Option<usize>, platforms: &[ToolchainPlatform]) -> ToolchainAvailableRelease {
+        ToolchainAvailableRelease {
+            release: release.parse().unwrap(),
+            revision: ToolchainRevision::new(revision),
+            platforms: platforms.iter().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn toolchain_from_releases() {
+        let config = ToolchainConfig::from_toml(
+            Utf8Path::new(TOOLCHAIN_CONFIG_FILE_NAME),
+            r#"
+                [toolchain]
+                variant = "llvmorg"
+                release = "^17"
+                components = ["llvm"]
+
+                [toolchain.targets.x86_64-macos]
+                revision = 1
+            "#,
+        )
+        .unwrap();
+        let releases = [
+            available("17.0.6", None, &[ToolchainPlatform::X86_64_LINUX_GNU]),
+            available("17.0.6", Some(1), &[ToolchainPlatform::X86_64_MACOS]),
+            available("17.0.7", None, &[ToolchainPlatform::X86_64_MACOS]),
+            available("18.1.8", None, &[ToolchainPlatform::X86_64_LINUX_GNU]),
+        ];
+
+        let linux = config
+            .toolchain_from_releases(ToolchainPlatform::X86_64_LINUX_GNU, &releases)
+            .unwrap();
+        assert_eq!(linux.context.release, ToolchainRelease::new(17, 0, Some(6)));
+        assert_eq!(linux.context.revision, ToolchainRevision::new(None));
+
+        let macos = config
+            .toolchain_from_releases(ToolchainPlatform::X86_64_MACOS, &releases)
+            .unwrap();
+        assert_eq!(macos.context.release, ToolchainRelease::new(17, 0, Some(6)));
+        assert_eq!(macos.context.revision, ToolchainRevision::new(Some(1)));
+
+        assert!(matches!(
+            config.toolchain_from_releases(ToolchainPlatform::ARM64_MACOS, &releases),
+            Err(Error::ToolchainConfigReleaseUnsatisfied { .. })
+        ));
+
+        assert_eq!(
+            config.platforms(ToolchainPlatform::X86_64_LINUX_GNU),
+            BTreeSet::from([ToolchainPlatform::X86_64_LINUX_GNU, ToolchainPlatform::X86_64_MACOS])
+        );
+    }
+}
@@ -0,0 +1,472 @@
+use camino::Utf8Path;
+use serde::{Deserialize, Serialize};
+use snafu::prelude::*;
+
+use crate::{
+    Checksums,
+    Toolchain,
+    ToolchainAvailableRelease,
+    ToolchainComponent,
+    ToolchainComponentAssetBundle,
+    ToolchainConfig,
+    ToolchainContext,
+    ToolchainPlatform,
+    ToolchainRelease,
+    ToolchainRevision,
+    ToolchainVariant,
+};
+
+pub const TOOLCHAIN_LOCKFILE_NAME: &str = "llvmup.lock";
+
+const TOOLCHAIN_LOCKFILE_VERSION: u32 = 1;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    HexDecode {
+        source: hex::FromHexError,
+    },
+    LlvmupConfig {
+        source: crate::config::Error,
+    },
+    LlvmupComponentAssetBundleDownloadChecksums {
+        source: crate::toolchain::component::asset::Error,
+    },
+    LlvmupDigestFromLockfile,
+    LlvmupDigestLoadChecksums {
+        source: crate::verification::Error,
+    },
+    TokioFsReadToString {
+        source: tokio::io::Error,
+    },
+    TokioFsWrite {
+        source: tokio::io::Error,
+    },
+    TomlFromStr {
+        source: toml::de::Error,
+    },
+    TomlToStringPretty {
+        source: toml::ser::Error,
+    },
+    ToolchainLockfileAssetFileNameMissing {
+        url: String,
+    },
+    ToolchainLockfileAssetUrlMismatch {
+        context: ToolchainContext,
+        expected: String,
+        actual: String,
+    },
+    ToolchainLockfileDigestMissing {
+        url: String,
+    },
+    ToolchainLockfileOutdated {
+        context: ToolchainContext,
+    },
+    ToolchainLockfilePlatformMissing {
+        platform: ToolchainPlatform,
+    },
+    ToolchainLockfileUnsupportedVersion {
+        version: u32,
+    },
+}
+
+impl Error {
+    /// Whether the lockfile no longer matches the configuration (and should be re-resolved), rather than being
+    /// unusable.
+    #[must_use]
+    pub fn is_outdated(&self) -> bool {
+        matches!(
+            self,
+            Self::ToolchainLockfileOutdated { .. } | Self::ToolchainLockfilePlatformMissing { .. }
+        )
+    }
+}
+
+impl From<self::Error> for crate::Error {
+    fn from(source: self::Error) -> Self {
+        crate::Error::LlvmupConfigLockfile { source }
+    }
+}
+
+/// The contents of an `llvmup.lock` file, pinning the toolchains resolved from an `llvmup.toml`.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ToolchainLockfile {
+    pub version: u32,
+    #[serde(default, rename = "toolchain")]
+    pub toolchains: Vec<ToolchainLock>,
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ToolchainLock {
+    #[serde(flatten, with = "lock_context")]
+    pub context: ToolchainContext,
+    #[serde(default, rename = "component")]
+    pub components: Vec<ToolchainLockComponent>,
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ToolchainLockComponent {
+    #[serde(rename = "name")]
+    pub component: ToolchainComponent,
+    pub url: String,
+    pub sha512: String,
+}
+
+/// The fields of a [`ToolchainContext`], which a lock spells out rather than using its string form.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct ToolchainLockContext {
+    variant: ToolchainVariant,
+    release: ToolchainRelease,
+    revision: ToolchainRevision,
+    platform: ToolchainPlatform,
+}
+
+mod lock_context {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::ToolchainLockContext;
+    use crate::ToolchainContext;
+
+    pub(super) fn serialize<S>(context: &ToolchainContext, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let ToolchainContext {
+            variant,
+            release,
+            revision,
+            platform,
+        } = *context;
+        ToolchainLockContext {
+            variant,
+            release,
+            revision,
+            platform,
+        }
+        .serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<ToolchainContext, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let ToolchainLockContext {
+            variant,
+            release,
+            revision,
+            platform,
+        } = ToolchainLockContext::deserialize(deserializer)?;
+        Ok(ToolchainContext::new(variant, release, revision, platform))
+    }
+}
+
+impl ToolchainLockfile {
+    #[must_use]
+    pub fn new(toolchains: Vec<ToolchainLock>) -> Self {
+        Self {
+            version: TOOLCHAIN_LOCKFILE_VERSION,
+            toolchains,
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn load(path: &Utf8Path) -> Result<Self, self::Error> {
+        let text = tokio::fs::read_to_string(path)
+            .await
+            .context(TokioFsReadToStringSnafu)?;
+        let lockfile = toml::from_str::<Self>(&text).context(TomlFromStrSnafu)?;
+        ensure!(
+            lockfile.version == TOOLCHAIN_LOCKFILE_VERSION,
+            ToolchainLockfileUnsupportedVersionSnafu {
+                version: lockfile.version
+            }
+        );
+        Ok(lockfile)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn store(&self, path: &Utf8Path) -> Result<(), self::Error> {
+        let text = toml::to_string_pretty(self).context(TomlToStringPrettySnafu)?;
+        tokio::fs::write(path, text).await.context(TokioFsWriteSnafu)?;
+        Ok(())
+    }
+
+    /// Replace the locks of the platforms `lockfile` locks with its own, keeping those of any other platform (e.g.,
+    /// locked by a teammate on another host).
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn update(&mut self, lockfile: ToolchainLockfile) {
+        for lock in lockfile.toolchains {
+            match self
+                .toolchains
+                .iter_mut()
+                .find(|locked| locked.context.platform == lock.context.platform)
+            {
+                Some(locked) => *locked = lock,
+                None => self.toolchains.push(lock),
+            }
+        }
+    }
+
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn toolchain(&self, context: &ToolchainContext) -> Option<&ToolchainLock> {
+        self.toolchains.iter().find(|lock| lock.context == *context)
+    }
+
+    /// The locked toolchains for `platforms`, as recorded rather than resolved anew, after checking that `config`
+    /// still allows each of them (e.g., that a locked release is still within a configured range).
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn config_toolchains<'a>(
+        &self,
+        config: &ToolchainConfig,
+        platforms: impl IntoIterator<Item = &'a ToolchainPlatform> + crate::LlvmupTracingDebug,
+    ) -> Result<Vec<Toolchain>, self::Error> {
+        let mut toolchains = vec![];
+        for platform in platforms.into_iter().copied() {
+            let lock = self
+                .toolchains
+                .iter()
+                .find(|lock| lock.context.platform == platform)
+                .context(ToolchainLockfilePlatformMissingSnafu { platform })?;
+            // NOTE: Resolving against the locked release alone yields the locked toolchain exactly when the
+            // configuration allows it.
+            let locked = ToolchainAvailableRelease {
+                release: lock.context.release,
+                revision: lock.context.revision,
+                platforms: [platform].into(),
+            };
+            let toolchain = match config.toolchain_from_releases(platform, &[locked]) {
+                Ok(toolchain) if lock.matches(&toolchain) => toolchain,
+                Ok(_) | Err(crate::config::Error::ToolchainConfigReleaseUnsatisfied { .. }) => {
+                    return ToolchainLockfileOutdatedSnafu { context: lock.context }.fail();
+                },
+                Err(source) => return Err(self::Error::LlvmupConfig { source }),
+            };
+            toolchains.push(toolchain);
+        }
+        Ok(toolchains)
+    }
+
+    /// Fail if resolving `toolchains` would produce anything other than what is recorded in the lockfile.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn ensure_locked<'a>(
+        &self,
+        toolchains: impl IntoIterator<Item = &'a Toolchain> + crate::LlvmupTracingDebug,
+    ) -> Result<(), self::Error> {
+        for toolchain in toolchains {
+            let locked = self
+                .toolchain(&toolchain.context)
+                .is_some_and(|lock| lock.matches(toolchain));
+            ensure!(locked, ToolchainLockfileOutdatedSnafu {
+                context: toolchain.context
+            });
+        }
+        Ok(())
+    }
+}
+
+impl ToolchainLock {
+    /// Record the asset URLs of `bundle` along with their digests from the bundle's `.sha512` checksums file.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn resolve(
+        bundle: &ToolchainComponentAssetBundle<'_>,
+        dirs: &crate::Directories,
+    ) -> Result<Self, self::Error> {
+        let checksums_text = bundle
+            .download_checksums(dirs)
+            .await
+            .context(LlvmupComponentAssetBundleDownloadChecksumsSnafu)?;
+        let checksums =
+            crate::verification::parse_sha512_checksums(&checksums_text).context(LlvmupDigestLoadChecksumsSnafu)?;
+
+        let components = bundle
+            .assets
+            .iter()
+            .map(|asset| ToolchainLockComponent::resolve(asset.component, asset.uri.to_string(), &checksums))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            context: *bundle.context,
+            components,
+        })
+    }
+
+    /// Whether `toolchain` resolves to exactly the context and component assets recorded in this lock.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn matches(&self, toolchain: &Toolchain) -> bool {
+        if self.context != toolchain.context || self.components.len() != toolchain.components.len() {
+            return false;
+        }
+        toolchain.components.iter().all(|component| {
+            component.asset_url(&toolchain.context).is_ok_and(|url| {
+                self.components
+                    .iter()
+                    .any(|locked| locked.component == *component && locked.url == url.as_str())
+            })
+        })
+    }
+
+    /// Ensure the asset URLs of `bundle` are the ones recorded in this lock.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn ensure_bundle(&self, bundle: &ToolchainComponentAssetBundle<'_>) -> Result<(), self::Error> {
+        for asset in &bundle.assets {
+            let actual = asset.uri.as_str();
+            let expected = self
+                .components
+                .iter()
+                .find(|locked| locked.component == asset.component)
+                .map(|locked| locked.url.as_str());
+            ensure!(expected == Some(actual), ToolchainLockfileAssetUrlMismatchSnafu {
+                context: *bundle.context,
+                expected: expected.unwrap_or_default().to_owned(),
+                actual: actual.to_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The locked digests, keyed by asset file name, for verifying downloads in place of the checksums file.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn checksums(&self) -> Result<Checksums<'_>, self::Error> {
+        let mut checksums = Checksums::new();
+        for component in &self.components {
+            let file_name = asset_file_name(&component.url).context(ToolchainLockfileAssetFileNameMissingSnafu {
+                url: component.url.clone(),
+            })?;
+            // NOTE: A component without a digest would otherwise be installed without any verification.
+            ensure!(!component.sha512.is_empty(), ToolchainLockfileDigestMissingSnafu {
+                url: component.url.clone(),
+            });
+            let bytes = hex::decode(&component.sha512).context(HexDecodeSnafu)?;
+            let digest =
+                crate::Sha512Digest::from_exact_iter(bytes).context(LlvmupDigestFromLockfileSnafu)?;
+            checksums.insert(file_name, digest);
+        }
+        Ok(checksums)
+    }
+}
+
+impl ToolchainLockComponent {
+    /// Lock the asset of `component` at `url`, whose digest must be listed in `checksums`.
+    fn resolve(component: ToolchainComponent, url: String, checksums: &Checksums<'_>) -> Result<Self, self::Error> {
+        let Some(digest) = asset_file_name(&url).and_then(|file_name| checksums.get(file_name)) else {
+            return ToolchainLockfileDigestMissingSnafu { url }.fail();
+        };
+        Ok(Self {
+            component,
+            url,
+            sha512: hex::encode(digest),
+        })
+    }
+}
+
+fn asset_file_name(url: &str) -> Option<&Utf8Path> {
+    url.rsplit('/').next().filter(|name| !name.is_empty()).map(Utf8Path::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TOOLCHAIN_CONFIG_FILE_NAME;
+
+    fn config(release: &str) -> ToolchainConfig {
+        let text = format!("[toolchain]\nvariant = \"llvmorg\"\nrelease = \"{release}\"\ncomponents = [\"llvm\"]\n");
+        ToolchainConfig::from_toml(Utf8Path::new(TOOLCHAIN_CONFIG_FILE_NAME), &text).unwrap()
+    }
+
+    fn lockfile(release: &str, platform: ToolchainPlatform) -> ToolchainLockfile {
+        let toolchain = config(release).toolchain_from_releases(platform, &[]).unwrap();
+        let components = toolchain
+            .components
+            .iter()
+            .map(|component| ToolchainLockComponent {
+                component: *component,
+                url: component.asset_url(&toolchain.context).unwrap().to_string(),
+                sha512: "00".repeat(64),
+            })
+            .collect();
+        ToolchainLockfile::new(vec![ToolchainLock {
+            context: toolchain.context,
+            components,
+        }])
+    }
+
+    #[test]
+    fn config_toolchains_locked() {
+        let platform = ToolchainPlatform::X86_64_LINUX_GNU;
+        let lockfile = lockfile("17.0.6", platform);
+
+        // NOTE: The locked release is used as is, however many newer releases the range would admit.
+        let toolchains = lockfile.config_toolchains(&config("^17"), &[platform]).unwrap();
+        assert_eq!(toolchains.len(), 1);
+        assert_eq!(toolchains[0].context.release, ToolchainRelease::new(17, 0, Some(6)));
+
+        for config in [config("^18"), config("17.0.5")] {
+            let err = lockfile.config_toolchains(&config, &[platform]).unwrap_err();
+            assert!(matches!(err, Error::ToolchainLockfileOutdated { .. }));
+        }
+
+        let err = lockfile
+            .config_toolchains(&config("^17"), &[ToolchainPlatform::X86_64_MACOS])
+            .unwrap_err();
+        assert!(matches!(err, Error::ToolchainLockfilePlatformMissing { .. }) && err.is_outdated());
+    }
+
+    #[test]
+    fn update_keeps_other_platforms() {
+        let mut locked = lockfile("17.0.6", ToolchainPlatform::X86_64_LINUX_GNU);
+        locked.update(lockfile("17.0.6", ToolchainPlatform::X86_64_MACOS));
+        locked.update(lockfile("18.1.8", ToolchainPlatform::X86_64_LINUX_GNU));
+
+        let contexts = locked.toolchains.iter().map(|lock| lock.context).collect::<Vec<_>>();
+        assert_eq!(contexts.len(), 2);
+        assert_eq!(contexts[0].platform, ToolchainPlatform::X86_64_LINUX_GNU);
+        assert_eq!(contexts[0].release, ToolchainRelease::new(18, 1, Some(8)));
+        assert_eq!(contexts[1].platform, ToolchainPlatform::X86_64_MACOS);
+        assert_eq!(contexts[1].release, ToolchainRelease::new(17, 0, Some(6)));
+    }
+
+    #[test]
+    fn resolve_requires_digest() {
+        let url = String::from("https://example.com/llvm-llvmorg-17.0.6-x86_64-linux-gnu.tar.xz");
+        let digest = crate::Sha512Digest::from_exact_iter([0xab; 64]).unwrap();
+        let mut checksums = Checksums::new();
+        checksums.insert(Utf8Path::new("llvm-llvmorg-17.0.6-x86_64-linux-gnu.tar.xz"), digest);
+
+        let locked = ToolchainLockComponent::resolve(ToolchainComponent::Llvm, url.clone(), &checksums).unwrap();
+        assert_eq!(locked.sha512, "ab".repeat(64));
+
+        let err = ToolchainLockComponent::resolve(ToolchainComponent::Llvm, url, &Checksums::new()).unwrap_err();
+        assert!(matches!(err, Error::ToolchainLockfileDigestMissing { .. }));
+    }
+
+    #[test]
+    fn checksums_require_digest() {
+        let mut locked = lockfile("17.0.6", ToolchainPlatform::X86_64_LINUX_GNU);
+        let components = locked.toolchains[0].components.len();
+        assert_eq!(locked.toolchains[0].checksums().unwrap().len(), components);
+
+        locked.toolchains[0].components[0].sha512 = String::new();
+        let err = locked.toolchains[0].checksums().unwrap_err();
+        assert!(matches!(err, Error::ToolchainLockfileDigestMissing { .. }));
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let mut locked = lockfile("17.0.6", ToolchainPlatform::X86_64_LINUX_GNU);
+        locked.toolchains[0].context.revision = ToolchainRevision::new(Some(2));
+        let text = toml::to_string_pretty(&locked).unwrap();
+        assert!(text.contains("[[toolchain]]\nvariant = \"llvmorg\"\nrelease = \"17.0.6\"\nrevision = \"+rev2\"\n"));
+        assert!(text.contains("platform = \"x86_64-linux-gnu\"\n"));
+
+        let parsed = toml::from_str::<ToolchainLockfile>(&text).unwrap();
+        assert!(parsed.toolchains == locked.toolchains);
+    }
+}
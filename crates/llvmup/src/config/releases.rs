@@ -0,0 +1,183 @@
+use serde::Deserialize;
+use snafu::prelude::*;
+use std::collections::BTreeSet;
+
+use crate::{ToolchainPlatform, ToolchainRelease, ToolchainRevision, ToolchainVariant};
+
+/// The GitHub API endpoint listing the releases of the toolchains repository.
+const TOOLCHAIN_RELEASES_URL: &str = "https://api.github.com/repos/llvmup/toolchains/releases";
+
+/// The number of releases requested per page (the maximum the API allows).
+const TOOLCHAIN_RELEASES_PER_PAGE: usize = 100;
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    ReqwestClientBuild { source: reqwest::Error },
+    ReqwestGet { source: reqwest::Error },
+    ReqwestGetErrorForStatus { source: reqwest::Error },
+    ReqwestText { source: reqwest::Error },
+    SerdeJsonFromStr { source: serde_json::Error },
+}
+
+impl From<self::Error> for crate::Error {
+    fn from(source: self::Error) -> Self {
+        crate::Error::LlvmupConfig {
+            source: crate::config::Error::LlvmupConfigReleases { source },
+        }
+    }
+}
+
+impl From<self::Error> for crate::config::Error {
+    fn from(source: self::Error) -> Self {
+        Self::LlvmupConfigReleases { source }
+    }
+}
+
+/// A release of a toolchain variant published in the toolchains repository, with the platforms it has assets for.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, Eq, PartialEq)]
+pub struct ToolchainAvailableRelease {
+    pub release: ToolchainRelease,
+    pub revision: ToolchainRevision,
+    pub platforms: BTreeSet<ToolchainPlatform>,
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<GithubReleaseAsset>,
+}
+
+#[derive(Deserialize)]
+struct GithubReleaseAsset {
+    name: String,
+}
+
+/// Fetch the releases of `variant` published in the toolchains repository.
+#[cfg_attr(feature = "tracing", tracing::instrument)]
+pub async fn fetch_available_releases(
+    variant: ToolchainVariant,
+) -> Result<Vec<ToolchainAvailableRelease>, self::Error> {
+    // NOTE: The API rejects requests without a user agent.
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("llvmup/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .context(ReqwestClientBuildSnafu)?;
+    let token = std::env::var("GITHUB_TOKEN").ok();
+
+    let mut releases = vec![];
+    for page in 1 .. {
+        let mut request = client
+            .get(TOOLCHAIN_RELEASES_URL)
+            .query(&[("per_page", TOOLCHAIN_RELEASES_PER_PAGE), ("page", page)]);
+        if let Some(token) = &token {
+            request = request.bearer_auth(token);
+        }
+        let text = request
+            .send()
+            .await
+            .context(ReqwestGetSnafu)?
+            .error_for_status()
+            .context(ReqwestGetErrorForStatusSnafu)?
+            .text()
+            .await
+            .context(ReqwestTextSnafu)?;
+        let page = serde_json::from_str::<Vec<GithubRelease>>(&text).context(SerdeJsonFromStrSnafu)?;
+        let last = page.len() < TOOLCHAIN_RELEASES_PER_PAGE;
+        releases.extend(available_releases(variant, page));
+        if last {
+            break;
+        }
+    }
+    Ok(releases)
+}
+
+/// The releases of `variant` among `releases`, whose platforms are those with a checksums file (as every platform of
+/// a release publishes one alongside its assets).
+fn available_releases(
+    variant: ToolchainVariant,
+    releases: Vec<GithubRelease>,
+) -> impl Iterator<Item = ToolchainAvailableRelease> {
+    releases.into_iter().filter_map(move |GithubRelease { tag_name, assets }| {
+        let (release, revision) = parse_release_name(variant, &tag_name)?;
+        let platforms = ToolchainPlatform::ALL
+            .into_iter()
+            .filter(|platform| {
+                let checksums = format!("{variant}-{release}-{platform}{revision}.sha512");
+                assets.iter().any(|asset| asset.name == checksums)
+            })
+            .collect();
+        Some(ToolchainAvailableRelease {
+            release,
+            revision,
+            platforms,
+        })
+    })
+}
+
+/// Parse a release tag or tree directory name of the form `<variant>-<release>[+rev<revision>]`, if it names a
+/// release of `variant`.
+pub(crate) fn parse_release_name(
+    variant: ToolchainVariant,
+    name: &str,
+) -> Option<(ToolchainRelease, ToolchainRevision)> {
+    let (name_variant, name_release) = name.split_once('-')?;
+    if name_variant != variant.to_string() {
+        return None;
+    }
+    let (release, revision) = match name_release.find('+') {
+        Some(index) => name_release.split_at(index),
+        None => (name_release, ""),
+    };
+    Some((release.parse().ok()?, revision.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_github_releases() {
+        let json = r#"[
+            {
+                "tag_name": "llvmorg-18.1.8",
+                "assets": [
+                    {"name": "llvmorg-18.1.8-x86_64-linux-gnu.sha512"},
+                    {"name": "llvm-llvmorg-18.1.8-x86_64-linux-gnu.tar.xz"},
+                    {"name": "llvmorg-18.1.8-arm64-macos.sha512"}
+                ]
+            },
+            {
+                "tag_name": "llvmorg-17.0.6+rev2",
+                "assets": [{"name": "llvmorg-17.0.6-x86_64-linux-gnu+rev2.sha512"}]
+            },
+            {"tag_name": "swift-5.10", "assets": []},
+            {"tag_name": "nightly"}
+        ]"#;
+        let releases = serde_json::from_str::<Vec<GithubRelease>>(json).unwrap();
+        let releases = available_releases(ToolchainVariant::Llvmorg, releases).collect::<Vec<_>>();
+
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].release, ToolchainRelease::new(18, 1, Some(8)));
+        assert_eq!(releases[0].revision, ToolchainRevision::new(None));
+        assert_eq!(
+            releases[0].platforms,
+            BTreeSet::from([ToolchainPlatform::ARM64_MACOS, ToolchainPlatform::X86_64_LINUX_GNU])
+        );
+        assert_eq!(releases[1].release, ToolchainRelease::new(17, 0, Some(6)));
+        assert_eq!(releases[1].revision, ToolchainRevision::new(Some(2)));
+        assert_eq!(releases[1].platforms, BTreeSet::from([ToolchainPlatform::X86_64_LINUX_GNU]));
+    }
+
+    #[test]
+    fn parse_release_names() {
+        assert_eq!(
+            parse_release_name(ToolchainVariant::Llvmorg, "llvmorg-17.0.6+rev1"),
+            Some((ToolchainRelease::new(17, 0, Some(6)), ToolchainRevision::new(Some(1))))
+        );
+        assert_eq!(parse_release_name(ToolchainVariant::Swift, "llvmorg-17.0.6"), None);
+        assert_eq!(parse_release_name(ToolchainVariant::Llvmorg, "llvmorg-17.0.6+1"), None);
+        assert_eq!(parse_release_name(ToolchainVariant::Llvmorg, "llvmorg"), None);
+    }
+}
@@ -2,7 +2,7 @@ use camino::{Utf8Path, Utf8PathBuf};
 use snafu::prelude::*;
 use std::path::PathBuf;
 
-use crate::{ToolchainComponent, ToolchainContext};
+use crate::{ToolchainComponent, ToolchainContext, ToolchainPlatform, ToolchainRelease};
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -20,6 +20,7 @@ pub enum Error {
 pub struct Directories {
     root: Utf8PathBuf,
     downloads: Utf8PathBuf,
+    manifests: Utf8PathBuf,
     toolchains: Utf8PathBuf,
     trees: Utf8PathBuf,
 }
@@ -40,11 +41,13 @@ impl Directories {
                 home_dir.join(".llvmup")
             };
         let downloads = root.join("downloads");
+        let manifests = root.join("manifests");
         let toolchains = root.join("toolchains");
         let trees = root.join("trees");
         Ok(Self {
             root,
             downloads,
+            manifests,
             toolchains,
             trees,
         })
@@ -62,6 +65,12 @@ impl Directories {
         &self.downloads
     }
 
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn manifests(&self) -> &Utf8Path {
+        &self.manifests
+    }
+
     #[must_use]
     #[cfg_attr(feature = "tracing", tracing::instrument)]
     pub fn toolchains(&self) -> &Utf8Path {
@@ -87,6 +96,13 @@ impl Directories {
         toolchain_release_path.join(platform.to_string())
     }
 
+    /// The tree `mold` is installed to, which is shared by all toolchains of `platform`.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn mold_root_path(&self, platform: ToolchainPlatform, release: ToolchainRelease) -> Utf8PathBuf {
+        self.trees().join(format!("mold-{release}")).join(platform.to_string())
+    }
+
     #[must_use]
     #[cfg_attr(feature = "tracing", tracing::instrument)]
     pub fn manifest_path(&self, context: ToolchainContext, component: ToolchainComponent) -> Utf8PathBuf {
@@ -95,6 +111,28 @@ impl Directories {
             .join(component.to_string())
             .join("llvmup.json")
     }
+
+    /// The directory standalone manifests for `context` are downloaded to, independent of any installed tree.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn manifest_cache_dir(&self, context: ToolchainContext) -> Utf8PathBuf {
+        let ToolchainContext {
+            variant,
+            release,
+            revision,
+            platform,
+        } = context;
+        self.manifests()
+            .join(format!("{variant}-{release}{revision}"))
+            .join(platform.to_string())
+    }
+
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn manifest_cache_path(&self, context: ToolchainContext, component: ToolchainComponent) -> Option<Utf8PathBuf> {
+        let file_name = component.manifest_file_name(&context)?;
+        Some(self.manifest_cache_dir(context).join(file_name))
+    }
 }
 
 #[cfg(feature = "logging")]
@@ -113,7 +151,7 @@ pub(crate) fn find_target_dir(out_dir: &Utf8Path) -> Result<Option<Utf8PathBuf>,
         if dir.join(".rustc_info.json").exists()
             || dir.join("CACHEDIR.TAG").exists()
             || dir.file_name() == Some("target")
-                && dir.parent().map_or(false, |parent| parent.join("Cargo.toml").exists())
+                && dir.parent().is_some_and(|parent| parent.join("Cargo.toml").exists())
         {
             return Ok(Some(dir));
         }
@@ -0,0 +1,69 @@
+//! Manifests and analyses for tests which don't need a toolchain tree.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::{
+    GenexContext,
+    OwnedToolchainAnalysis,
+    OwnedToolchainComponentManifests,
+    ToolchainComponent,
+    ToolchainContext,
+    ToolchainHandle,
+    ToolchainPlatform,
+    ToolchainRelease,
+    ToolchainRevision,
+    ToolchainVariant,
+};
+
+/// An imported target of `kind` (e.g., `STATIC_LIBRARY`) at `location` (in the `Release` configuration), linking
+/// `libraries` (a comma separated list of JSON strings), with any `extra` properties (a comma separated list of JSON
+/// members).
+pub(crate) fn target(name: &str, kind: &str, libraries: &str, location: &str, extra: &str) -> String {
+    let extra = if extra.is_empty() {
+        String::new()
+    } else {
+        format!("{extra},")
+    };
+    format!(
+        r#""{name}": {{
+            "llvmupTargetKind": "inherent",
+            "IMPORTED": "TRUE",
+            "NAME": "{name}",
+            "SYSTEM": "FALSE",
+            "TYPE": "{kind}",
+            "IMPORTED_CONFIGURATIONS": ["RELEASE"],
+            "INTERFACE_LINK_LIBRARIES": [{libraries}],
+            "LOCATION": "{location}",
+            "IMPORTED_LOCATION_RELEASE": "{location}",
+            {extra}
+            "MACOSX_PACKAGE_LOCATION": "",
+            "VS_DEPLOYMENT_LOCATION": ""
+        }}"#
+    )
+}
+
+/// The manifests of a toolchain whose `llvm` component has the imported `targets`.
+pub(crate) fn manifests(targets: &[String]) -> Arc<OwnedToolchainComponentManifests> {
+    let manifest = format!(r#"{{"cmakeProperties": {{"IMPORTED_TARGETS": {{{}}}}}}}"#, targets.join(","));
+    let sources = BTreeMap::from([(ToolchainComponent::Llvm, manifest)]);
+    Arc::new(OwnedToolchainComponentManifests::parse(sources).unwrap())
+}
+
+/// The analysis of `manifests` for `platform`, in the `Release` configuration.
+pub(crate) fn analysis(
+    platform: ToolchainPlatform,
+    manifests: Arc<OwnedToolchainComponentManifests>,
+) -> OwnedToolchainAnalysis {
+    let genex_context = GenexContext::new(platform).config("Release");
+    OwnedToolchainAnalysis::new(ToolchainHandle { hash: 0 }, genex_context, manifests).unwrap()
+}
+
+/// The context of an `llvmorg-17.0.6` toolchain for `platform`.
+pub(crate) fn context(platform: ToolchainPlatform) -> ToolchainContext {
+    ToolchainContext::new(
+        ToolchainVariant::Llvmorg,
+        ToolchainRelease::new(17, 0, Some(6)),
+        ToolchainRevision::new(None),
+        platform,
+    )
+}
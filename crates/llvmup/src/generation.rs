@@ -1,14 +1,35 @@
+use glob::Pattern;
+use petgraph::visit::{Dfs, Walker};
 use snafu::prelude::*;
 use std::collections::{BTreeMap, BTreeSet};
 
-use crate::{analysis::ToolchainComponentDependencyNode, ToolchainComponent, ToolchainContext};
+use crate::{
+    analysis::{
+        closure::ToolchainLinkClosure,
+        genex::GenexContext,
+        ToolchainComponentDependencyGraph,
+        ToolchainComponentDependencyNode,
+    },
+    ToolchainComponent,
+    ToolchainContext,
+};
 
 mod archives;
+pub(crate) mod bindgen;
 pub(crate) mod cargo;
+pub(crate) mod cc;
+pub(crate) mod cmake;
+pub(crate) mod drift;
+pub(crate) mod pkg_config;
 
 #[derive(Debug, Snafu)]
 pub enum Error {
+    GlobPatternNew { source: glob::PatternError, pattern: String },
+    LlvmupGenerationBindgen { source: bindgen::Error },
     LlvmupGenerationCargo { source: cargo::Error },
+    LlvmupGenerationCc { source: cc::Error },
+    LlvmupGenerationCMake { source: cmake::Error },
+    LlvmupGenerationPkgConfig { source: pkg_config::Error },
 }
 
 impl From<self::Error> for crate::Error {
@@ -22,8 +43,12 @@ pub struct ToolchainConfigGenerator<'a> {
     context: ToolchainContext,
     directories: &'a crate::Directories,
     external_targets: &'a BTreeSet<&'a str>,
+    toolchain_dependencies: &'a ToolchainComponentDependencyGraph<'a>,
+    genex_context: GenexContext<'a>,
     toolchain_dependencies_postorder_sccs: Vec<Vec<ToolchainComponentDependencyNode<'a>>>,
     toolchain_components_crate_dependencies: BTreeMap<ToolchainComponent, &'a [&'a str]>,
+    allowed_targets: Vec<Pattern>,
+    denied_targets: Vec<Pattern>,
 }
 
 impl<'a> ToolchainConfigGenerator<'a> {
@@ -33,6 +58,8 @@ impl<'a> ToolchainConfigGenerator<'a> {
         context: ToolchainContext,
         directories: &'a crate::Directories,
         external_targets: &'a BTreeSet<&'a str>,
+        toolchain_dependencies: &'a ToolchainComponentDependencyGraph<'a>,
+        genex_context: GenexContext<'a>,
         toolchain_dependencies_postorder_sccs: Vec<Vec<ToolchainComponentDependencyNode<'a>>>,
         toolchain_components_crate_dependencies: BTreeMap<ToolchainComponent, &'a [&'a str]>,
     ) -> Self {
@@ -40,8 +67,160 @@ impl<'a> ToolchainConfigGenerator<'a> {
             context,
             directories,
             external_targets,
+            toolchain_dependencies,
+            genex_context,
             toolchain_dependencies_postorder_sccs,
             toolchain_components_crate_dependencies,
+            allowed_targets: Vec::new(),
+            denied_targets: Vec::new(),
         }
     }
+
+    /// Restrict generation to the targets of `closure`, so that only what they need is linked.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn with_link_closure(mut self, closure: &ToolchainLinkClosure<'a>) -> Self {
+        self.toolchain_dependencies_postorder_sccs.clone_from(&closure.postorder_sccs);
+        self
+    }
+
+    /// Only generate for the targets matching (by name or glob pattern, e.g., `LLVM*CodeGen`) any of `patterns`,
+    /// along with what they depend on.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn allow_targets<'p>(
+        mut self,
+        patterns: impl IntoIterator<Item = &'p str> + crate::LlvmupTracingDebug,
+    ) -> Result<Self, self::Error> {
+        self.allowed_targets.extend(Self::patterns(patterns)?);
+        Ok(self)
+    }
+
+    /// Skip the targets matching (by name or glob pattern, e.g., `clang*`) any of `patterns`, unless an allowed target
+    /// depends on them.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn deny_targets<'p>(
+        mut self,
+        patterns: impl IntoIterator<Item = &'p str> + crate::LlvmupTracingDebug,
+    ) -> Result<Self, self::Error> {
+        self.denied_targets.extend(Self::patterns(patterns)?);
+        Ok(self)
+    }
+
+    fn patterns<'p>(patterns: impl IntoIterator<Item = &'p str>) -> Result<Vec<Pattern>, self::Error> {
+        patterns
+            .into_iter()
+            .map(|pattern| Pattern::new(pattern).context(GlobPatternNewSnafu { pattern }))
+            .collect()
+    }
+
+    /// The strongly connected components to generate for, after applying the allow and deny lists.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    fn selected_postorder_sccs(&self) -> Vec<Vec<ToolchainComponentDependencyNode<'a>>> {
+        if self.allowed_targets.is_empty() && self.denied_targets.is_empty() {
+            return self.toolchain_dependencies_postorder_sccs.clone();
+        }
+        let matches = |patterns: &[Pattern], name: &str| patterns.iter().any(|pattern| pattern.matches(name));
+        let roots = self
+            .toolchain_dependencies_postorder_sccs
+            .iter()
+            .flatten()
+            .filter(|node| node.component.is_some())
+            .filter(|node| self.allowed_targets.is_empty() || matches(&self.allowed_targets, node.name))
+            .filter(|node| !matches(&self.denied_targets, node.name));
+        let mut selected = BTreeSet::new();
+        for root in roots {
+            selected.extend(Dfs::new(self.toolchain_dependencies, *root).iter(self.toolchain_dependencies));
+        }
+        // NOTE: Keep within the current SCCs, which may already have been restricted to a link closure.
+        self.toolchain_dependencies_postorder_sccs
+            .iter()
+            .filter_map(|scc| {
+                let scc = scc.iter().filter(|node| selected.contains(*node)).copied().collect::<Vec<_>>();
+                (!scc.is_empty()).then_some(scc)
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "analysis"))]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{analysis, context, manifests, target},
+        Llvmup,
+        OwnedToolchainAnalysis,
+        ToolchainPlatform,
+    };
+    use camino::Utf8Path;
+
+    /// `LLVMX86CodeGen` links `LLVMCore`, which links `LLVMSupport`, as do `LLVMOption` and `clangBasic`.
+    fn selection_analysis() -> OwnedToolchainAnalysis {
+        let manifests = manifests(&[
+            target("LLVMX86CodeGen", "STATIC_LIBRARY", r#""LLVMCore""#, "lib/libLLVMX86CodeGen.a", ""),
+            target("LLVMCore", "STATIC_LIBRARY", r#""LLVMSupport""#, "lib/libLLVMCore.a", ""),
+            target("LLVMOption", "STATIC_LIBRARY", r#""LLVMSupport""#, "lib/libLLVMOption.a", ""),
+            target("clangBasic", "STATIC_LIBRARY", r#""LLVMSupport""#, "lib/libclangBasic.a", ""),
+            target("LLVMSupport", "STATIC_LIBRARY", "", "lib/libLLVMSupport.a", ""),
+        ]);
+        analysis(ToolchainPlatform::X86_64_LINUX_GNU, manifests)
+    }
+
+    /// The names of the selected targets, in name order.
+    fn selected<'a>(generator: &ToolchainConfigGenerator<'a>) -> Vec<&'a str> {
+        let mut names = generator
+            .selected_postorder_sccs()
+            .into_iter()
+            .flatten()
+            .map(|node| node.name)
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn selected_postorder_sccs() {
+        let analysis = selection_analysis();
+        let llvmup = Llvmup::builder().root(Utf8Path::new("/llvmup")).build().unwrap();
+        let generator = || {
+            let platform = ToolchainPlatform::X86_64_LINUX_GNU;
+            llvmup
+                .generator(context(platform), analysis.get(), BTreeSet::new(), BTreeMap::new())
+                .unwrap()
+        };
+
+        assert_eq!(selected(&generator()), [
+            "LLVMCore",
+            "LLVMOption",
+            "LLVMSupport",
+            "LLVMX86CodeGen",
+            "clangBasic"
+        ]);
+
+        // NOTE: Allowed targets bring along what they depend on.
+        let allowed = generator().allow_targets(["LLVM*CodeGen"]).unwrap();
+        assert_eq!(selected(&allowed), ["LLVMCore", "LLVMSupport", "LLVMX86CodeGen"]);
+
+        let denied = generator().deny_targets(["clang*", "LLVMOption"]).unwrap();
+        assert_eq!(selected(&denied), ["LLVMCore", "LLVMSupport", "LLVMX86CodeGen"]);
+
+        // NOTE: A denied target is kept if an allowed one depends on it.
+        let both = generator()
+            .allow_targets(["LLVMX86CodeGen"])
+            .unwrap()
+            .deny_targets(["LLVMCore"])
+            .unwrap();
+        assert_eq!(selected(&both), ["LLVMCore", "LLVMSupport", "LLVMX86CodeGen"]);
+
+        let closure = analysis.get().link_closure(["LLVMOption", "clangBasic"]).unwrap();
+        let restricted = generator()
+            .with_link_closure(&closure)
+            .allow_targets(["LLVM*"])
+            .unwrap();
+        assert_eq!(selected(&restricted), ["LLVMOption", "LLVMSupport"]);
+
+        assert!(matches!(
+            generator().allow_targets(["LLVM[X86"]),
+            Err(Error::GlobPatternNew { pattern, .. }) if pattern == "LLVM[X86"
+        ));
+    }
 }
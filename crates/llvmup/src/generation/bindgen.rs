@@ -0,0 +1,285 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use quote::ToTokens;
+use rust_format::Formatter;
+use snafu::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    generation::cc::LLVM_COMPILE_DEFINITIONS,
+    toolchain::platform::ToolchainSys,
+    ToolchainComponent,
+    ToolchainConfigGenerator,
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    LlvmupAnalysisGenex { source: crate::analysis::genex::Error },
+    RustFormat { source: rust_format::Error },
+    TokioFsWrite { source: tokio::io::Error },
+}
+
+impl From<self::Error> for crate::Error {
+    fn from(source: self::Error) -> Self {
+        Self::LlvmupGeneration {
+            source: crate::generation::Error::LlvmupGenerationBindgen { source },
+        }
+    }
+}
+
+impl From<self::Error> for crate::generation::Error {
+    fn from(source: self::Error) -> Self {
+        Self::LlvmupGenerationBindgen { source }
+    }
+}
+
+/// The prefixes of the C API items each component provides, for use as bindgen allowlist patterns.
+fn c_api_patterns(component: ToolchainComponent) -> &'static [&'static str] {
+    match component {
+        ToolchainComponent::Llvm => &["LLVM.*", "lto_.*", "thinlto_.*"],
+        ToolchainComponent::Clang => &["clang_.*", "CX.*"],
+        ToolchainComponent::Mlir => &["mlir.*", "Mlir.*"],
+        _ => &[],
+    }
+}
+
+impl ToolchainConfigGenerator<'_> {
+    /// Compute the `bindgen::Builder` configuration for the C APIs of `components`: the include directories and
+    /// definitions their targets require, the clang arguments for the toolchain's platform, and allowlists.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn generate_bindgen_config(
+        &self,
+        components: impl IntoIterator<Item = ToolchainComponent> + crate::LlvmupTracingDebug,
+    ) -> Result<BindgenConfig<'_>, self::Error> {
+        let components = components.into_iter().collect::<BTreeSet<_>>();
+
+        let mut include_dirs = BTreeSet::new();
+        let mut system_include_dirs = BTreeSet::new();
+        let mut defines = BTreeMap::new();
+        let nodes = self
+            .selected_postorder_sccs()
+            .into_iter()
+            .flatten()
+            .filter(|node| node.component.is_some_and(|component| components.contains(&component)));
+        for node in nodes {
+            include_dirs.extend(node.interface_include_directories.iter());
+            system_include_dirs.extend(node.interface_system_include_directories.iter());
+            for definition in node.interface_compile_definitions.iter() {
+                for definition in self
+                    .genex_context
+                    .evaluate_list_item(definition)
+                    .context(LlvmupAnalysisGenexSnafu)?
+                {
+                    let definition = definition.strip_prefix("-D").unwrap_or(&definition);
+                    let (name, value) = match definition.split_once('=') {
+                        Some((name, value)) => (name, Some(String::from(value))),
+                        None => (definition, None),
+                    };
+                    defines.insert(String::from(name), value);
+                }
+            }
+        }
+        if !components.is_empty() {
+            for definition in LLVM_COMPILE_DEFINITIONS {
+                defines.insert(String::from(definition), None);
+            }
+        }
+
+        let clang_args = vec![format!("--target={}", self.context.platform.llvm_target_triple())];
+
+        let allowlist = components
+            .iter()
+            .flat_map(|component| c_api_patterns(*component).iter().copied())
+            .collect();
+
+        // NOTE: libclang is a shared library, which isn't part of the dependency graph unless something links it, so
+        // its directory is derived from the platform's layout otherwise.
+        let libclang_dir = components.contains(&ToolchainComponent::Clang).then(|| {
+            let location = self
+                .toolchain_dependencies
+                .nodes()
+                .find(|node| node.component == Some(ToolchainComponent::Clang) && node.name == "libclang")
+                .and_then(|node| node.location)
+                .and_then(|location| Utf8Path::new(location).parent().map(Utf8Path::to_path_buf));
+            match (location, self.context.platform.sys()) {
+                (Some(dir), _) => dir,
+                (None, ToolchainSys::Windows) => Utf8PathBuf::from("bin"),
+                (None, ToolchainSys::Linux | ToolchainSys::Macos) => Utf8PathBuf::from("lib"),
+            }
+        });
+
+        Ok(BindgenConfig {
+            context: self.context,
+            directories: self.directories,
+            include_dirs,
+            system_include_dirs,
+            defines,
+            clang_args,
+            allowlist,
+            libclang_dir,
+        })
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct BindgenConfig<'a> {
+    pub context: crate::ToolchainContext,
+    pub directories: &'a crate::Directories,
+    pub include_dirs: BTreeSet<&'a str>,
+    pub system_include_dirs: BTreeSet<&'a str>,
+    pub defines: BTreeMap<String, Option<String>>,
+    /// Further clang arguments (e.g., `--target`).
+    pub clang_args: Vec<String>,
+    /// Patterns matching the functions, types and variables of the components' C APIs.
+    pub allowlist: Vec<&'static str>,
+    /// The directory containing libclang (relative to the toolchain tree), if the `clang` component was selected.
+    pub libclang_dir: Option<Utf8PathBuf>,
+}
+
+impl BindgenConfig<'_> {
+    /// All the clang arguments to pass to bindgen, including those for include directories and definitions.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn all_clang_args(&self) -> Vec<String> {
+        let root = self.directories.toolchain_root_path(self.context);
+        let mut args = self.clang_args.clone();
+        args.extend(self.include_dirs.iter().map(|dir| format!("-I{}", root.join(dir))));
+        for dir in &self.system_include_dirs {
+            args.extend([String::from("-isystem"), root.join(dir).to_string()]);
+        }
+        args.extend(self.defines.iter().map(|(name, value)| match value {
+            Some(value) => format!("-D{name}={value}"),
+            None => format!("-D{name}"),
+        }));
+        args
+    }
+
+    /// The toolchain tree, relative to the llvmup root.
+    fn relative_toolchain_root_path(&self) -> Utf8PathBuf {
+        let root = self.directories.toolchain_root_path(self.context);
+        root.strip_prefix(self.directories.root())
+            .map_or_else(|_| root.clone(), Utf8Path::to_path_buf)
+    }
+
+    /// Write `build_llvmup_bindgen.rs`, which configures a `bindgen::Builder` and locates the toolchain's libclang.
+    pub async fn emit(&self, cargo_manifest_dir: &Utf8Path) -> Result<(), self::Error> {
+        let contents = self.render()?;
+        let bindgen_build_path = cargo_manifest_dir.join("build_llvmup_bindgen.rs");
+        tokio::fs::write(bindgen_build_path, contents)
+            .await
+            .context(TokioFsWriteSnafu)?;
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn render(&self) -> Result<String, self::Error> {
+        let file: syn::File = {
+            // NOTE: Paths within the toolchain tree are joined onto its root when the build script runs, so that the
+            // root of the machine the file was generated on isn't baked into it.
+            let tree = self.relative_toolchain_root_path();
+            let tree = tree.components().map(|component| component.as_str());
+            let include_dirs = &self.include_dirs;
+            let system_include_dirs = &self.system_include_dirs;
+            let clang_args = self.clang_args.iter().cloned().chain(self.defines.iter().map(|(name, value)| {
+                match value {
+                    Some(value) => format!("-D{name}={value}"),
+                    None => format!("-D{name}"),
+                }
+            }));
+            let allowlist = &self.allowlist;
+            let libclang_expr: syn::Expr = match &self.libclang_dir {
+                Some(dir) => {
+                    let dir = dir.components().map(|component| component.as_str());
+                    syn::parse_quote!(Some(llvmup_bindgen_toolchain_root()#(.join(#dir))*))
+                },
+                None => syn::parse_quote!(None),
+            };
+
+            syn::parse_quote! {
+                #![allow(clippy::all)]
+                #![allow(clippy::pedantic)]
+
+                /// Configure `builder` for the toolchain's headers, restricted to the selected C APIs.
+                #[allow(unused)]
+                pub fn llvmup_bindgen_builder(builder: bindgen::Builder) -> bindgen::Builder {
+                    let root = llvmup_bindgen_toolchain_root();
+                    builder
+                        .clang_args([#(#clang_args),*])
+                        #(.clang_arg(format!("-I{}", root.join(#include_dirs).display())))*
+                        #(.clang_arg("-isystem").clang_arg(root.join(#system_include_dirs).display().to_string()))*
+                        #(.allowlist_function(#allowlist).allowlist_type(#allowlist).allowlist_var(#allowlist))*
+                }
+
+                /// The directory containing the toolchain's libclang, if it was selected, for the build script to
+                /// set `LIBCLANG_PATH` to (before it spawns any threads) so that bindgen loads it.
+                #[allow(unused)]
+                pub fn llvmup_bindgen_libclang() -> Option<std::path::PathBuf> {
+                    #libclang_expr
+                }
+
+                /// The toolchain tree, under `LLVMUP_HOME` (or `~/.llvmup`) on the machine running the build script.
+                #[allow(unused)]
+                pub fn llvmup_bindgen_toolchain_root() -> std::path::PathBuf {
+                    println!("cargo:rerun-if-env-changed=LLVMUP_HOME");
+                    let root = match std::env::var_os("LLVMUP_HOME") {
+                        Some(home) => std::path::PathBuf::from(home),
+                        None => {
+                            let home = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+                            let home = std::env::var_os(home).expect("the home directory should be known");
+                            std::path::PathBuf::from(home).join(".llvmup")
+                        },
+                    };
+                    root #(.join(#tree))*
+                }
+            }
+        };
+
+        let tokens = file.to_token_stream();
+        rust_format::RustFmt::default()
+            .format_tokens(tokens)
+            .context(RustFormatSnafu)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{analysis, context, manifests, target},
+        Llvmup,
+        ToolchainPlatform,
+    };
+
+    #[test]
+    fn render_resolves_toolchain_root_at_build_time() {
+        let manifests = manifests(&[
+            target(
+                "LLVMSupport",
+                "STATIC_LIBRARY",
+                r#""LLVMDemangle""#,
+                "lib/libLLVMSupport.a",
+                r#""INTERFACE_INCLUDE_DIRECTORIES": ["include"]"#,
+            ),
+            target("LLVMDemangle", "STATIC_LIBRARY", "", "lib/libLLVMDemangle.a", ""),
+        ]);
+        let platform = ToolchainPlatform::X86_64_LINUX_GNU;
+        let analysis = analysis(platform, manifests);
+        let llvmup = Llvmup::builder().root(Utf8Path::new("/llvmup")).build().unwrap();
+        let generator = llvmup
+            .generator(context(platform), analysis.get(), BTreeSet::new(), BTreeMap::new())
+            .unwrap();
+        let config = generator
+            .generate_bindgen_config([ToolchainComponent::Llvm, ToolchainComponent::Clang])
+            .unwrap();
+        let rendered = config.render().unwrap();
+
+        assert!(!rendered.contains("/llvmup"), "{rendered}");
+        assert!(!rendered.contains("set_var"), "{rendered}");
+        assert!(
+            rendered.contains(r#"root.join("trees").join("llvmorg-17.0.6").join("x86_64-linux-gnu")"#),
+            "{rendered}"
+        );
+        assert!(rendered.contains(r#"root.join("include").display()"#), "{rendered}");
+        assert!(rendered.contains(r#"Some(llvmup_bindgen_toolchain_root().join("lib"))"#), "{rendered}");
+        assert!(rendered.contains(r#""--target=x86_64-unknown-linux-gnu""#), "{rendered}");
+    }
+}
@@ -2,25 +2,41 @@ use camino::{Utf8Path, Utf8PathBuf};
 use quote::ToTokens;
 use rust_format::Formatter;
 use snafu::prelude::*;
-use std::collections::BTreeSet;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+};
 
-use crate::{analysis::ToolchainComponentDependencyNode, ToolchainConfigGenerator};
+use crate::{
+    analysis::{
+        backends::{target_backends, ToolchainTargetBackendLibrary, ToolchainTargetBackends},
+        ToolchainComponentDependencyNode,
+    },
+    generation::drift::GeneratedFileDrift,
+    toolchain::platform::{ToolchainArch, ToolchainSys},
+    ToolchainComponent,
+    ToolchainConfigGenerator,
+    ToolchainContext,
+    ToolchainPlatform,
+};
 
 #[derive(Debug, Snafu)]
 pub enum Error {
     CargoManifestDoesNotExist { path: Utf8PathBuf },
-    CargoManifestFeatureSectionNotFound,
+    CargoManifestFeaturesNotATable,
+    CargoConfigMergeEmpty,
+    CargoConfigMergeLinkOrderConflict { directives: Vec<String> },
+    CargoConfigMergeContextMismatch {
+        expected: ToolchainContext,
+        actual: ToolchainContext,
+    },
     CaminoUtf8PathTryExists { source: std::io::Error },
     LlvmupAnalysis { source: crate::analysis::Error },
+    LlvmupAnalysisGenex { source: crate::analysis::genex::Error },
     RustFormat { source: rust_format::Error },
-    StdNumTryFromInt { source: core::num::TryFromIntError },
-    TokioFsFileSetLen { source: tokio::io::Error },
-    TokioFsOpenOptions { source: tokio::io::Error },
+    TokioFsReadToString { source: tokio::io::Error },
     TokioFsWrite { source: tokio::io::Error },
-    TokioIoReadToString { source: tokio::io::Error },
-    TokioIoWriteAll { source: tokio::io::Error },
-    TomlToStringPretty { source: toml::ser::Error },
+    TomlEditParse { source: toml_edit::TomlError },
 }
 
 impl From<self::Error> for crate::Error {
@@ -39,30 +55,65 @@ impl From<self::Error> for crate::generation::Error {
 
 impl<'a> ToolchainConfigGenerator<'a> {
     #[cfg_attr(feature = "tracing", tracing::instrument)]
-    pub fn generate_cargo_config(&self) -> Result<CargoConfig<'_>, self::Error> {
+    pub fn generate_cargo_config(&self) -> Result<CargoConfig<'a>, self::Error> {
         let mut build_link_dirs = BTreeSet::new();
         let mut build_link_items = Vec::new();
         let mut cargo_features = toml::Table::default();
 
-        for target_sccs in &self.toolchain_dependencies_postorder_sccs {
+        let postorder_sccs = self.selected_postorder_sccs();
+        for target_sccs in &postorder_sccs {
             Self::compute_cargo_features_and_build_link_dirs(
                 self,
                 target_sccs,
                 &mut cargo_features,
                 &mut build_link_dirs,
             )?;
-            Self::compute_cargo_build_link_items(self, target_sccs, &mut build_link_items)?;
+            let feature_gate = true;
+            Self::compute_cargo_build_link_items(self, target_sccs, feature_gate, &mut build_link_items)?;
         }
+        let target_backends = Self::compute_cargo_target_backend_features(&postorder_sccs, &mut cargo_features);
 
         Ok(CargoConfig {
             context: self.context,
             directories: self.directories,
-            build_link_dirs,
+            platforms: BTreeSet::from([self.context.platform]),
+            configs: BTreeMap::from([(self.context.platform, BTreeSet::from([self.genex_context.config]))]),
+            build_link_dirs: BTreeMap::from([(self.context.platform, build_link_dirs)]),
             build_link_items,
             cargo_features,
+            target_backends,
         })
     }
 
+    /// Group the libraries of each LLVM target backend into a `target-<backend>` feature, and all of those into a
+    /// `target-all` feature.
+    pub fn compute_cargo_target_backend_features(
+        target_sccs: &[Vec<ToolchainComponentDependencyNode<'a>>],
+        cargo_features: &mut toml::Table,
+    ) -> ToolchainTargetBackends<'a> {
+        let targets = target_sccs
+            .iter()
+            .flatten()
+            .filter(|node| node.component == Some(ToolchainComponent::Llvm))
+            .map(|node| node.name)
+            .collect::<Vec<_>>();
+        let target_backends = target_backends(targets);
+        let mut all = Vec::<toml::Value>::new();
+        for (backend, libraries) in &target_backends {
+            let feature = format!("target-{}", backend.to_ascii_lowercase());
+            let libraries = libraries
+                .values()
+                .map(|library| toml::Value::String(String::from(*library)))
+                .collect();
+            cargo_features.insert(feature.clone(), toml::Value::Array(libraries));
+            all.push(toml::Value::String(feature));
+        }
+        if !all.is_empty() {
+            cargo_features.insert(String::from("target-all"), toml::Value::Array(all));
+        }
+        target_backends
+    }
+
     pub fn compute_cargo_features_and_build_link_dirs(
         &self,
         target_sccs: &[ToolchainComponentDependencyNode<'a>],
@@ -78,17 +129,34 @@ impl<'a> ToolchainConfigGenerator<'a> {
 
             let mut dependent_features = Vec::<toml::Value>::new();
 
-            for library in itertools::sorted(node.interface_link_libraries.iter().map(|library| {
-                library
-                    .strip_prefix("$<LINK_ONLY:")
-                    .and_then(|library| library.strip_suffix('>'))
-                    .unwrap_or(library)
-            })) {
+            let mut libraries = vec![];
+            for library in node.interface_link_libraries.iter() {
+                libraries.extend(
+                    self.genex_context
+                        .evaluate_list_item(library)
+                        .context(LlvmupAnalysisGenexSnafu)?,
+                );
+            }
+            libraries.sort();
+            libraries.dedup();
+            for library in libraries {
                 // Filter external targets so they are not added as cargo features.
-                if self.external_targets.contains(library) {
+                if self.external_targets.contains(library.as_ref()) {
+                    continue;
+                }
+                // NOTE: As in the dependency analysis, a computed name (e.g., `lib$<1:m>`) only has a feature if it
+                // names a target of the toolchain.
+                if matches!(library, Cow::Owned(_))
+                    && !self
+                        .toolchain_dependencies
+                        .nodes()
+                        .any(|node| node.component.is_some() && node.name == library)
+                {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(%library, "dropping a computed non-target library");
                     continue;
                 }
-                dependent_features.push(toml::Value::String(String::from(library)));
+                dependent_features.push(toml::Value::String(library.into_owned()));
             }
 
             let index = String::from(node.name);
@@ -109,68 +177,387 @@ impl<'a> ToolchainConfigGenerator<'a> {
 
     pub fn compute_cargo_build_link_items(
         &self,
-        target_sccs: &[ToolchainComponentDependencyNode<'_>],
-        cargo_features_build_link_items: &mut Vec<syn::Stmt>,
+        target_sccs: &[ToolchainComponentDependencyNode<'a>],
+        feature_gate: bool,
+        cargo_features_build_link_items: &mut Vec<CargoBuildLinkItem<'a>>,
     ) -> Result<(), self::Error> {
-        match target_sccs {
-            [] => {},
-            [node] => {
-                let feature_gate = true;
-                if let Some(item) = node
-                    .emit_cargo_link_instruction(feature_gate)
-                    .context(LlvmupAnalysisSnafu)?
-                {
-                    cargo_features_build_link_items.push(item);
-                }
-            },
-            nodes => {
-                let metas = nodes.iter().map(|node| -> syn::MetaNameValue {
-                    let lib = node.name;
-                    syn::parse_quote!(feature = #lib)
-                });
-
-                let feature_gate = false;
-                let items = nodes
-                    .iter()
-                    .map(|node| -> Result<Option<syn::Stmt>, self::Error> {
-                        node.emit_cargo_link_instruction(feature_gate)
-                            .context(LlvmupAnalysisSnafu)
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
-
-                cargo_features_build_link_items.push(syn::parse_quote!(
-                    #[cfg(any(#(#metas),*))]
-                    {
-                        println!("cargo:rustc-link-arg=-Wl,--start-group");
-                        #(#items)*
-                        println!("cargo:rustc-link-arg=-Wl,--end-group");
-                    }
-                ));
-            },
+        // NOTE: Gates are attached when the items are emitted, so the statements themselves are ungated.
+        let features = if feature_gate {
+            target_sccs.iter().map(|node| node.name).collect()
+        } else {
+            Vec::new()
+        };
+        let configs = BTreeMap::from([(self.context.platform, BTreeSet::from([self.genex_context.config]))]);
+        let mut directives = Vec::new();
+        for node in target_sccs {
+            directives.extend(self.node_link_directives(node)?);
+        }
+        if directives.is_empty() {
+            return Ok(());
+        }
+        // NOTE: GNU ld (and lld in its ELF flavor) only resolves cyclic references between archives within an
+        // explicit group. ld64 and link.exe search all archives until no undefined symbols remain, so cycles resolve
+        // natively (and neither accepts the group flags).
+        if target_sccs.len() > 1 && self.context.platform.sys() == ToolchainSys::Linux {
+            directives.insert(0, String::from("cargo:rustc-link-arg=-Wl,--start-group"));
+            directives.push(String::from("cargo:rustc-link-arg=-Wl,--end-group"));
         }
+        cargo_features_build_link_items.push(CargoBuildLinkItem {
+            features,
+            configs,
+            directives,
+        });
         Ok(())
     }
+
+    fn node_link_directives(&self, node: &ToolchainComponentDependencyNode<'a>) -> Result<Vec<String>, self::Error> {
+        let objects = node.cargo_link_objects(&self.directories.toolchain_root_path(self.context));
+        if !objects.is_empty() {
+            return Ok(objects);
+        }
+        let instruction = node
+            .cargo_link_instruction(self.context.platform)
+            .context(LlvmupAnalysisSnafu)?;
+        Ok(instruction.into_iter().collect())
+    }
+}
+
+/// The `cargo:rustc-link-*` directives linking a target (or a dependency cycle of targets), together with the
+/// features, platforms, and build configurations they must be gated on.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct CargoBuildLinkItem<'a> {
+    /// The item is emitted if any of these features is enabled (or unconditionally if there are none).
+    pub features: Vec<&'a str>,
+    /// The build configurations, per platform, whose analysis produced the item.
+    pub configs: BTreeMap<ToolchainPlatform, BTreeSet<&'a str>>,
+    pub directives: Vec<String>,
+}
+
+impl CargoBuildLinkItem<'_> {
+    /// Whether the item is enabled, given the features enabled for the crate being built.
+    #[must_use]
+    pub fn enabled(&self, feature_enabled: impl Fn(&str) -> bool) -> bool {
+        self.features.is_empty() || self.features.iter().any(|feature| feature_enabled(feature))
+    }
+
+    /// Print the directives as a build script statement.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn stmt(&self) -> syn::Stmt {
+        let directives = &self.directives;
+        if let [directive] = directives.as_slice() {
+            syn::parse_quote!(println!(#directive);)
+        } else {
+            syn::parse_quote!({
+                #(println!(#directives);)*
+            })
+        }
+    }
+
+    /// Emit the item gated at compile time on features and platforms, and at run time (via the `config` selected by
+    /// the build script) on the configurations it is restricted to, if any.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    fn emit(&self, all_configs: &BTreeMap<ToolchainPlatform, BTreeSet<&str>>) -> Vec<syn::Stmt> {
+        let feature_meta: Option<syn::Meta> = match self.features.as_slice() {
+            [] => None,
+            [feature] => Some(syn::parse_quote!(feature = #feature)),
+            features => Some(syn::parse_quote!(any(#(feature = #features),*))),
+        };
+        let all_platforms = all_configs.keys().copied().collect::<BTreeSet<_>>();
+
+        // NOTE: Platforms sharing the same configuration restriction (`None` when the item is common to all of a
+        // platform's configurations) can share a single statement.
+        let mut groups = BTreeMap::<Option<&BTreeSet<&str>>, BTreeSet<ToolchainPlatform>>::new();
+        for (platform, configs) in &self.configs {
+            let restriction = if all_configs.get(platform) == Some(configs) {
+                None
+            } else {
+                Some(configs)
+            };
+            groups.entry(restriction).or_default().insert(*platform);
+        }
+
+        let stmt = &self.stmt();
+        groups
+            .into_iter()
+            .map(|(restriction, platforms)| {
+                let platform_meta = if platforms == all_platforms {
+                    None
+                } else {
+                    Some(emit_platforms_cfg_predicate(&platforms, &all_platforms))
+                };
+                let gate: Option<syn::Attribute> = match (&feature_meta, platform_meta) {
+                    (None, None) => None,
+                    (Some(meta), None) => Some(syn::parse_quote!(#[cfg(#meta)])),
+                    (None, Some(meta)) => Some(syn::parse_quote!(#[cfg(#meta)])),
+                    (Some(feature_meta), Some(platform_meta)) => {
+                        Some(syn::parse_quote!(#[cfg(all(#feature_meta, #platform_meta))]))
+                    },
+                };
+                if let Some(configs) = restriction {
+                    let configs = configs.iter();
+                    syn::parse_quote! {
+                        #gate
+                        if matches!(config.as_str(), #(#configs)|*) {
+                            #stmt
+                        }
+                    }
+                } else {
+                    syn::parse_quote! {
+                        #gate
+                        #stmt
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn merge_key(&self) -> (Vec<&str>, &[String]) {
+        (self.features.clone(), &self.directives)
+    }
+}
+
+/// Render the `cfg` predicate matching exactly `platforms` out of `all_platforms`.
+///
+/// A `target_os` predicate is used when every analysed platform of that system is referenced, otherwise platforms are
+/// distinguished by `target_arch` (which is unique per system among the supported platforms). Either is qualified by
+/// `target_env`, so that targets with another C library or ABI (e.g., `musl`, or `gnu` on Windows) don't match.
+fn emit_platforms_cfg_predicate(
+    platforms: &BTreeSet<ToolchainPlatform>,
+    all_platforms: &BTreeSet<ToolchainPlatform>,
+) -> syn::Meta {
+    let mut predicates = Vec::<syn::Meta>::new();
+    let systems = platforms.iter().map(ToolchainPlatform::sys).collect::<BTreeSet<_>>();
+    for sys in systems {
+        let sys_platforms = platforms
+            .iter()
+            .filter(|platform| platform.sys() == sys)
+            .collect::<BTreeSet<_>>();
+        let all_sys_platforms = all_platforms
+            .iter()
+            .filter(|platform| platform.sys() == sys)
+            .collect::<BTreeSet<_>>();
+        if sys_platforms == all_sys_platforms {
+            predicates.push(emit_sys_cfg_predicate(sys, None));
+        } else {
+            for platform in sys_platforms {
+                predicates.push(emit_sys_cfg_predicate(sys, Some(platform.arch())));
+            }
+        }
+    }
+    if let [predicate] = predicates.as_slice() {
+        predicate.clone()
+    } else {
+        syn::parse_quote!(any(#(#predicates),*))
+    }
+}
+
+fn emit_platform_cfg_predicate(platform: ToolchainPlatform) -> syn::Meta {
+    emit_sys_cfg_predicate(platform.sys(), Some(platform.arch()))
+}
+
+fn emit_sys_cfg_predicate(sys: ToolchainSys, arch: Option<ToolchainArch>) -> syn::Meta {
+    let mut predicates = Vec::<syn::Meta>::new();
+    let target_os = cfg_target_os(sys);
+    predicates.push(syn::parse_quote!(target_os = #target_os));
+    if let Some(target_env) = cfg_target_env(sys) {
+        predicates.push(syn::parse_quote!(target_env = #target_env));
+    }
+    if let Some(arch) = arch {
+        let target_arch = cfg_target_arch(arch);
+        predicates.push(syn::parse_quote!(target_arch = #target_arch));
+    }
+    if let [predicate] = predicates.as_slice() {
+        predicate.clone()
+    } else {
+        syn::parse_quote!(all(#(#predicates),*))
+    }
+}
+
+pub(crate) fn cfg_target_os(sys: ToolchainSys) -> &'static str {
+    match sys {
+        ToolchainSys::Linux => "linux",
+        ToolchainSys::Macos => "macos",
+        ToolchainSys::Windows => "windows",
+    }
+}
+
+/// The `target_env` of the toolchains built for `sys`, if it has one (Apple targets don't).
+pub(crate) fn cfg_target_env(sys: ToolchainSys) -> Option<&'static str> {
+    match sys {
+        ToolchainSys::Linux => Some("gnu"),
+        ToolchainSys::Macos => None,
+        ToolchainSys::Windows => Some("msvc"),
+    }
+}
+
+pub(crate) fn cfg_target_arch(arch: ToolchainArch) -> &'static str {
+    match arch {
+        ToolchainArch::Aarch64 | ToolchainArch::Arm64 => "aarch64",
+        ToolchainArch::Arm => "arm",
+        ToolchainArch::I686 => "x86",
+        ToolchainArch::PowerPc64Le => "powerpc64",
+        ToolchainArch::RiscV64 => "riscv64",
+        ToolchainArch::S390X => "s390x",
+        ToolchainArch::X86_64 => "x86_64",
+    }
 }
 
 #[cfg_attr(feature = "debug", derive(Debug))]
 pub struct CargoConfig<'a> {
     pub context: crate::ToolchainContext,
     pub directories: &'a crate::Directories,
-    pub build_link_dirs: BTreeSet<&'a str>,
-    pub build_link_items: Vec<syn::Stmt>,
+    pub platforms: BTreeSet<ToolchainPlatform>,
+    /// The build configurations analysed for each platform.
+    pub configs: BTreeMap<ToolchainPlatform, BTreeSet<&'a str>>,
+    pub build_link_dirs: BTreeMap<ToolchainPlatform, BTreeSet<&'a str>>,
+    pub build_link_items: Vec<CargoBuildLinkItem<'a>>,
     pub cargo_features: toml::Table,
+    /// The LLVM target backends with libraries among the generated features.
+    pub target_backends: ToolchainTargetBackends<'a>,
 }
 
-const CARGO_TOML_FEATURE_SECTION: &str = "#@llvmup:features\n";
+const BUILD_LLVMUP_FILE_NAME: &str = "build_llvmup.rs";
+const LLVMUP_TARGETS_FILE_NAME: &str = "llvmup_targets.rs";
+
+/// The comment lines delimiting the region of the `[features]` table of `Cargo.toml` owned by llvmup.
+const CARGO_TOML_FEATURES_BEGIN: &str = "#@llvmup:features";
+const CARGO_TOML_FEATURES_END: &str = "#@llvmup:features:end";
+
+impl CargoConfig<'_> {
+    /// Merge configurations generated for several platforms (or build configurations) of the same toolchain into one
+    /// whose link items are gated on the platforms and configurations that reference them, so that a single
+    /// `build_llvmup.rs` is correct for all of them.
+    ///
+    /// The link order of every configuration is kept, so merging fails if two of them link the same items in
+    /// conflicting orders.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn merge(configs: impl IntoIterator<Item = Self> + crate::LlvmupTracingDebug) -> Result<Self, self::Error> {
+        let mut configs = configs.into_iter();
+        let mut merged = configs.next().context(CargoConfigMergeEmptySnafu)?;
+        // NOTE: The link order of each configuration, as indices into the merged items.
+        let mut link_orders = vec![(0 .. merged.build_link_items.len()).collect::<Vec<_>>()];
+
+        for config in configs {
+            let expected = ToolchainContext {
+                platform: config.context.platform,
+                ..merged.context
+            };
+            ensure!(config.context == expected, CargoConfigMergeContextMismatchSnafu {
+                expected,
+                actual: config.context,
+            });
+
+            merged.platforms.extend(config.platforms);
+
+            for (platform, configs) in config.configs {
+                merged.configs.entry(platform).or_default().extend(configs);
+            }
+
+            for (platform, dirs) in config.build_link_dirs {
+                merged.build_link_dirs.entry(platform).or_default().extend(dirs);
+            }
+
+            let mut link_order = Vec::with_capacity(config.build_link_items.len());
+            for item in config.build_link_items {
+                let key = item.merge_key();
+                if let Some(index) = merged
+                    .build_link_items
+                    .iter()
+                    .position(|merged| merged.merge_key() == key)
+                {
+                    for (platform, configs) in item.configs {
+                        merged.build_link_items[index]
+                            .configs
+                            .entry(platform)
+                            .or_default()
+                            .extend(configs);
+                    }
+                    link_order.push(index);
+                } else {
+                    link_order.push(merged.build_link_items.len());
+                    merged.build_link_items.push(item);
+                }
+            }
+            link_orders.push(link_order);
+
+            for (backend, libraries) in config.target_backends {
+                merged.target_backends.entry(backend).or_default().extend(libraries);
+            }
+
+            for (feature, dependencies) in config.cargo_features {
+                match (merged.cargo_features.get_mut(&feature), dependencies) {
+                    (Some(toml::Value::Array(merged)), toml::Value::Array(dependencies)) => {
+                        for dependency in dependencies {
+                            if !merged.contains(&dependency) {
+                                merged.push(dependency);
+                            }
+                        }
+                    },
+                    (Some(_), _) => {},
+                    (None, dependencies) => {
+                        merged.cargo_features.insert(feature, dependencies);
+                    },
+                }
+            }
+        }
+
+        merged.build_link_items = merge_link_orders(merged.build_link_items, &link_orders)?;
+        Ok(merged)
+    }
 
-impl<'a> CargoConfig<'a> {
+    /// Emit `build_llvmup.rs`, `llvmup_targets.rs`, and the features of `Cargo.toml`.
     pub async fn emit(&self, cargo_manifest_dir: &Utf8Path) -> Result<(), self::Error> {
         self.emit_build_llvmup(cargo_manifest_dir).await?;
         self.emit_cargo_features(cargo_manifest_dir).await?;
+        self.emit_llvmup_targets(cargo_manifest_dir).await?;
+        Ok(())
+    }
+
+    /// The check mode of [`CargoConfig::emit`]: render the files in memory and compare them against those in
+    /// `cargo_manifest_dir` instead of writing them.
+    ///
+    /// Only the files which are out of date are returned, so no drift means nothing to regenerate. A missing
+    /// `build_llvmup.rs` or `llvmup_targets.rs` is taken to be unused (e.g., `build_llvmup.rs` by crates linking
+    /// through [`crate::build`]) rather than out of date.
+    pub async fn check(&self, cargo_manifest_dir: &Utf8Path) -> Result<Vec<GeneratedFileDrift>, self::Error> {
+        let mut drifts = vec![];
+
+        let cargo_build_path = cargo_manifest_dir.join(BUILD_LLVMUP_FILE_NAME);
+        if let Some(actual) = read_to_string_if_exists(&cargo_build_path).await? {
+            drifts.extend(GeneratedFileDrift::new(cargo_build_path, Some(actual), self.render_build_llvmup()?));
+        }
+
+        let cargo_manifest_path = cargo_manifest_dir.join("Cargo.toml");
+        let actual = read_to_string_if_exists(&cargo_manifest_path)
+            .await?
+            .context(CargoManifestDoesNotExistSnafu {
+                path: cargo_manifest_path.clone(),
+            })?;
+        let expected = self.render_cargo_features(&actual)?;
+        drifts.extend(GeneratedFileDrift::new(cargo_manifest_path, Some(actual), expected));
+
+        let cargo_targets_path = cargo_manifest_dir.join(LLVMUP_TARGETS_FILE_NAME);
+        if let Some(actual) = read_to_string_if_exists(&cargo_targets_path).await? {
+            drifts.extend(GeneratedFileDrift::new(cargo_targets_path, Some(actual), self.render_llvmup_targets()?));
+        }
+
+        Ok(drifts)
+    }
+
+    /// Emit `build_llvmup.rs`, which links the libraries enabled through features from the toolchain tree this
+    /// configuration was generated for. Crates linking through [`crate::build`] instead don't need it.
+    pub async fn emit_build_llvmup(&self, cargo_manifest_dir: &Utf8Path) -> Result<(), self::Error> {
+        let contents = self.render_build_llvmup()?;
+        let cargo_build_path = cargo_manifest_dir.join(BUILD_LLVMUP_FILE_NAME);
+        tokio::fs::write(cargo_build_path, contents)
+            .await
+            .context(TokioFsWriteSnafu)?;
         Ok(())
     }
 
-    async fn emit_build_llvmup(&self, cargo_manifest_dir: &Utf8Path) -> Result<(), self::Error> {
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn render_build_llvmup(&self) -> Result<String, self::Error> {
         // TODO:
         // let ToolchainContext {
         //     variant,
@@ -180,12 +567,66 @@ impl<'a> CargoConfig<'a> {
         // } = self.context;
 
         let file: syn::File = {
-            let rustc_link_search_stmts = self.build_link_dirs.iter().map(|path| -> syn::Stmt {
-                let dir = self.directories.toolchain_root_path(self.context).join(path);
-                let rustc_link_search = format!("cargo:rustc-link-search=native={dir}");
-                syn::parse_quote!(println!(#rustc_link_search);)
+            let rustc_link_search_stmts = self.build_link_dirs.iter().flat_map(|(platform, paths)| {
+                let context = ToolchainContext {
+                    platform: *platform,
+                    ..self.context
+                };
+                let root = self.directories.toolchain_root_path(context);
+                let platform_gate: Option<syn::Attribute> = if self.platforms.len() > 1 {
+                    let meta = emit_platform_cfg_predicate(*platform);
+                    Some(syn::parse_quote!(#[cfg(#meta)]))
+                } else {
+                    None
+                };
+                paths.iter().map(move |path| -> syn::Stmt {
+                    let dir = root.join(path);
+                    let rustc_link_search = format!("cargo:rustc-link-search=native={dir}");
+                    syn::parse_quote! {
+                        #platform_gate
+                        println!(#rustc_link_search);
+                    }
+                })
             });
-            let rustc_link_lib_stmts = self.build_link_items.iter();
+            let rustc_link_lib_stmts = self
+                .build_link_items
+                .iter()
+                .flat_map(|item| item.emit(&self.configs));
+
+            // NOTE: With several configurations to choose from, the build script picks one from Cargo's profile,
+            // unless overridden through `LLVMUP_CONFIG`.
+            let (llvmup_config_fn, llvmup_config_stmt): (Option<syn::Item>, Option<syn::Stmt>) =
+                if self.configs.values().any(|configs| configs.len() > 1) {
+                    let all_configs = self.configs.values().flatten().copied().collect::<BTreeSet<_>>();
+                    let select = |preferred: &[&'static str]| -> &str {
+                        preferred
+                            .iter()
+                            .find(|config| all_configs.contains(*config))
+                            .or_else(|| all_configs.first())
+                            .copied()
+                            .unwrap_or_default()
+                    };
+                    let debug_config = select(&["Debug", "RelWithDebInfo"]);
+                    let release_config = select(&["Release", "MinSizeRel", "RelWithDebInfo"]);
+                    (
+                        Some(syn::parse_quote! {
+                            #[allow(unused)]
+                            pub fn llvmup_config() -> String {
+                                println!("cargo:rerun-if-env-changed=LLVMUP_CONFIG");
+                                if let Ok(config) = std::env::var("LLVMUP_CONFIG") {
+                                    return config;
+                                }
+                                match std::env::var("PROFILE").as_deref() {
+                                    Ok("release") => String::from(#release_config),
+                                    _ => String::from(#debug_config),
+                                }
+                            }
+                        }),
+                        Some(syn::parse_quote!(let config = llvmup_config();)),
+                    )
+                } else {
+                    (None, None)
+                };
 
             syn::parse_quote! {
                 #![allow(clippy::all)]
@@ -204,25 +645,88 @@ impl<'a> CargoConfig<'a> {
 
                 #[allow(unused)]
                 pub fn rustc_link_libs() {
+                    #llvmup_config_stmt
                     #(#rustc_link_lib_stmts)*
                 }
+
+                #llvmup_config_fn
             }
         };
 
         let tokens = file.to_token_stream();
-        let contents = rust_format::RustFmt::default()
+        rust_format::RustFmt::default()
             .format_tokens(tokens)
-            .context(RustFormatSnafu)?;
+            .context(RustFormatSnafu)
+    }
 
-        let cargo_build_path = cargo_manifest_dir.join("build_llvmup.rs");
-        tokio::fs::write(cargo_build_path, contents)
+    /// Emit `llvmup_targets.rs`, which lists the target backends enabled through features (for inclusion with
+    /// `include!`), so that only those are initialized.
+    pub async fn emit_llvmup_targets(&self, cargo_manifest_dir: &Utf8Path) -> Result<(), self::Error> {
+        let contents = self.render_llvmup_targets()?;
+        let cargo_targets_path = cargo_manifest_dir.join(LLVMUP_TARGETS_FILE_NAME);
+        tokio::fs::write(cargo_targets_path, contents)
             .await
             .context(TokioFsWriteSnafu)?;
-
         Ok(())
     }
 
-    async fn emit_cargo_features(&self, cargo_manifest_dir: &Utf8Path) -> Result<(), self::Error> {
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn render_llvmup_targets(&self) -> Result<String, self::Error> {
+        let targets = self.target_backends.iter().map(|(backend, libraries)| -> syn::Expr {
+            let library = |library: ToolchainTargetBackendLibrary| -> syn::Expr {
+                match libraries.get(&library) {
+                    Some(library) => syn::parse_quote!(cfg!(feature = #library)),
+                    None => syn::parse_quote!(false),
+                }
+            };
+            let info = libraries.get(&ToolchainTargetBackendLibrary::Info);
+            let asm_parser = library(ToolchainTargetBackendLibrary::AsmParser);
+            let code_gen = library(ToolchainTargetBackendLibrary::CodeGen);
+            let desc = library(ToolchainTargetBackendLibrary::Desc);
+            let disassembler = library(ToolchainTargetBackendLibrary::Disassembler);
+            syn::parse_quote! {
+                #[cfg(feature = #info)]
+                LlvmupTarget {
+                    name: #backend,
+                    asm_parser: #asm_parser,
+                    code_gen: #code_gen,
+                    desc: #desc,
+                    disassembler: #disassembler,
+                }
+            }
+        });
+
+        let file: syn::File = syn::parse_quote! {
+            /// An LLVM target backend whose `TargetInfo` is enabled, and which of its other libraries are.
+            #[allow(unused)]
+            #[derive(Clone, Copy, Debug)]
+            pub struct LlvmupTarget {
+                /// The backend name, as in `LLVMInitialize<name>TargetInfo`.
+                pub name: &'static str,
+                /// Whether `LLVMInitialize<name>AsmParser` is available.
+                pub asm_parser: bool,
+                /// Whether `LLVMInitialize<name>Target` and `LLVMInitialize<name>AsmPrinter` are available.
+                pub code_gen: bool,
+                /// Whether `LLVMInitialize<name>TargetMC` is available.
+                pub desc: bool,
+                /// Whether `LLVMInitialize<name>Disassembler` is available.
+                pub disassembler: bool,
+            }
+
+            /// The enabled LLVM target backends.
+            #[allow(unused)]
+            pub const LLVMUP_TARGETS: &[LlvmupTarget] = &[#(#targets),*];
+        };
+
+        let tokens = file.to_token_stream();
+        rust_format::RustFmt::default()
+            .format_tokens(tokens)
+            .context(RustFormatSnafu)
+    }
+
+    /// Emit the features into the region of the `[features]` table of `Cargo.toml` delimited by the
+    /// `#@llvmup:features` and `#@llvmup:features:end` comments (appending the region if there is none yet).
+    pub async fn emit_cargo_features(&self, cargo_manifest_dir: &Utf8Path) -> Result<(), self::Error> {
         let cargo_manifest_path = cargo_manifest_dir.join("Cargo.toml");
 
         if !cargo_manifest_path.try_exists().context(CaminoUtf8PathTryExistsSnafu)? {
@@ -231,36 +735,614 @@ impl<'a> CargoConfig<'a> {
             });
         }
 
-        let mut cargo_manifest = tokio::fs::OpenOptions::new()
-            .read(true)
-            .write(true)
-            .append(true)
-            .open(cargo_manifest_path)
+        let cargo_manifest = tokio::fs::read_to_string(&cargo_manifest_path)
             .await
-            .context(TokioFsOpenOptionsSnafu)?;
-
-        if let Some(pos) = {
-            let mut text = String::new();
-            cargo_manifest
-                .read_to_string(&mut text)
-                .await
-                .context(TokioIoReadToStringSnafu)?;
-            text.find(CARGO_TOML_FEATURE_SECTION)
-        } {
-            // Truncate the file to the section header before writing the features.
-            let len = u64::try_from(pos + CARGO_TOML_FEATURE_SECTION.len()).context(StdNumTryFromIntSnafu)?;
-            cargo_manifest.set_len(len).await.context(TokioFsFileSetLenSnafu)?;
-        } else {
-            return Err(self::Error::CargoManifestFeatureSectionNotFound);
+            .context(TokioFsReadToStringSnafu)?;
+        let cargo_manifest = self.render_cargo_features(&cargo_manifest)?;
+        tokio::fs::write(cargo_manifest_path, cargo_manifest)
+            .await
+            .context(TokioFsWriteSnafu)?;
+
+        Ok(())
+    }
+
+    /// Render `cargo_manifest` with the features replacing those of its llvmup region, leaving everything else
+    /// (including user features and comments) as it was.
+    ///
+    /// Features the user defines outside the region take precedence over generated ones of the same name. Without any
+    /// features to generate, the (empty) region is kept in place.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn render_cargo_features(&self, cargo_manifest: &str) -> Result<String, self::Error> {
+        let mut document = cargo_manifest
+            .parse::<toml_edit::DocumentMut>()
+            .context(TomlEditParseSnafu)?;
+        let features_position = document
+            .entry("features")
+            .or_insert_with(|| {
+                let mut table = toml_edit::Table::new();
+                table.decor_mut().set_prefix("\n");
+                toml_edit::Item::Table(table)
+            })
+            .as_table()
+            .context(CargoManifestFeaturesNotATableSnafu)?
+            .position();
+        // NOTE: A region without any features is left as its markers, after the last feature of the table.
+        let dangling_begin_prefix = split_begin_marker(&features_table_end_prefix(&mut document, features_position))
+            .map(|(begin_prefix, _)| begin_prefix);
+        let features = document["features"]
+            .as_table_mut()
+            .context(CargoManifestFeaturesNotATableSnafu)?;
+
+        let names = features.iter().map(|(name, _)| String::from(name)).collect::<Vec<_>>();
+        let has_marker = |name: &String, marker: &str| {
+            features
+                .key(name)
+                .and_then(|key| key.leaf_decor().prefix())
+                .and_then(toml_edit::RawString::as_str)
+                .is_some_and(|prefix| has_marker_line(prefix, marker))
+        };
+        // NOTE: Without an end marker, the region extends to the end of the table, as the marker used to be the last
+        // line of `Cargo.toml`.
+        let region = names
+            .iter()
+            .position(|name| has_marker(name, CARGO_TOML_FEATURES_BEGIN))
+            .map(|begin| {
+                let end = names[begin + 1 ..]
+                    .iter()
+                    .position(|name| has_marker(name, CARGO_TOML_FEATURES_END))
+                    .map_or(names.len(), |end| begin + 1 + end);
+                begin .. end
+            })
+            .or_else(|| dangling_begin_prefix.as_ref().map(|_| names.len() .. names.len()));
+
+        let mut entries = names
+            .iter()
+            .filter_map(|name| features.remove_entry(name))
+            .collect::<Vec<_>>();
+        let (before, region, after) = match region {
+            Some(region) => {
+                let after = entries.split_off(region.end);
+                let region = entries.split_off(region.start);
+                (entries, region, after)
+            },
+            None => (entries, Vec::new(), Vec::new()),
+        };
+        let user_defined = before
+            .iter()
+            .chain(&after)
+            .map(|(key, _)| key.get())
+            .collect::<BTreeSet<_>>();
+        let generated = self
+            .cargo_features
+            .iter()
+            .filter(|(name, _)| !user_defined.contains(name.as_str()))
+            .collect::<Vec<_>>();
+
+        // NOTE: Whatever precedes the begin marker (e.g., blank lines or the user's comments) is kept with it.
+        let begin_prefix = region
+            .first()
+            .and_then(|(key, _)| key.leaf_decor().prefix())
+            .and_then(toml_edit::RawString::as_str)
+            .map(|prefix| strip_marker_lines(prefix, CARGO_TOML_FEATURES_END))
+            .or(dangling_begin_prefix)
+            .unwrap_or_else(|| {
+                let separator = if before.is_empty() { "" } else { "\n" };
+                format!("{separator}{CARGO_TOML_FEATURES_BEGIN}\n")
+            });
+        // NOTE: Without generated features, both markers precede whatever follows the region.
+        let end_prefix = if generated.is_empty() { begin_prefix.as_str() } else { "" };
+        let mut end_prefix = Some(format!("{end_prefix}{CARGO_TOML_FEATURES_END}\n"));
+
+        for (key, item) in before {
+            features.insert_formatted(&key, item);
+        }
+        for (index, (name, dependencies)) in generated.iter().enumerate() {
+            let mut key = toml_edit::Key::new(name.as_str());
+            if index == 0 {
+                key.leaf_decor_mut().set_prefix(begin_prefix.as_str());
+            }
+            features.insert_formatted(&key, toml_edit::Item::Value(cargo_feature_value(dependencies)));
+        }
+        for (index, (mut key, item)) in after.into_iter().enumerate() {
+            let prefix = key
+                .leaf_decor()
+                .prefix()
+                .and_then(toml_edit::RawString::as_str)
+                .unwrap_or_default();
+            let mut prefix = strip_marker_lines(prefix, CARGO_TOML_FEATURES_BEGIN);
+            if index == 0 {
+                prefix = strip_marker_lines(&prefix, CARGO_TOML_FEATURES_END);
+                if let Some(end_prefix) = end_prefix.take() {
+                    prefix.insert_str(0, &end_prefix);
+                }
+            }
+            key.leaf_decor_mut().set_prefix(prefix);
+            features.insert_formatted(&key, item);
         }
 
-        let features_text = toml::to_string_pretty(&self.cargo_features).context(TomlToStringPrettySnafu)?;
+        mark_features_table_end(&mut document, features_position, end_prefix.as_deref());
+
+        Ok(document.to_string())
+    }
+}
+
+/// Order `items` such that every one of `link_orders` (indices into `items`) is kept, which is what keeps the link
+/// order of each merged platform intact. Where they leave the order open, items stay in the order they were first
+/// seen in.
+fn merge_link_orders<'a>(
+    items: Vec<CargoBuildLinkItem<'a>>,
+    link_orders: &[Vec<usize>],
+) -> Result<Vec<CargoBuildLinkItem<'a>>, self::Error> {
+    let mut successors = vec![BTreeSet::new(); items.len()];
+    let mut predecessors = vec![0_usize; items.len()];
+    for link_order in link_orders {
+        for pair in link_order.windows(2) {
+            if pair[0] != pair[1] && successors[pair[0]].insert(pair[1]) {
+                predecessors[pair[1]] += 1;
+            }
+        }
+    }
 
-        cargo_manifest
-            .write_all(features_text.as_bytes())
+    let mut ready = (0 .. items.len())
+        .filter(|index| predecessors[*index] == 0)
+        .collect::<BTreeSet<_>>();
+    let mut order = Vec::with_capacity(items.len());
+    while let Some(index) = ready.pop_first() {
+        order.push(index);
+        for successor in &successors[index] {
+            predecessors[*successor] -= 1;
+            if predecessors[*successor] == 0 {
+                ready.insert(*successor);
+            }
+        }
+    }
+
+    // NOTE: Whatever is left is ordered differently by different platforms (or follows such items).
+    if order.len() < items.len() {
+        let directives = (0 .. items.len())
+            .filter(|index| predecessors[*index] > 0)
+            .flat_map(|index| items[index].directives.iter().cloned())
+            .collect();
+        return Err(self::Error::CargoConfigMergeLinkOrderConflict { directives });
+    }
+
+    let mut items = items.into_iter().map(Some).collect::<Vec<_>>();
+    Ok(order.into_iter().filter_map(|index| items[index].take()).collect())
+}
+
+/// Place the end marker after the `[features]` table (at `features_position`) if `marked`, and remove it otherwise.
+///
+/// Comments following the last key of a table belong to whatever follows the table (i.e., the next table header, or
+/// the end of the document), so that is where the end marker goes when the region ends the table.
+fn mark_features_table_end(
+    document: &mut toml_edit::DocumentMut,
+    features_position: Option<usize>,
+    end_prefix: Option<&str>,
+) {
+    let following_prefix = features_table_end_prefix(document, features_position);
+    // NOTE: A region left without features by a previous generation is replaced along with its begin prefix.
+    let following_prefix = split_begin_marker(&following_prefix).map_or(following_prefix, |(_, rest)| rest);
+    let mut following_prefix = strip_marker_lines(&following_prefix, CARGO_TOML_FEATURES_END);
+    if let Some(end_prefix) = end_prefix {
+        following_prefix.insert_str(0, end_prefix);
+    }
+    let following = features_position.and_then(|position| next_table_position(document.as_table(), position));
+    match following.and_then(|position| table_at_position(document.as_table_mut(), position)) {
+        Some(table) => table.decor_mut().set_prefix(following_prefix),
+        None => document.set_trailing(following_prefix),
+    }
+}
+
+/// The text between the last entry of the `[features]` table and whatever follows it (i.e., the next table's
+/// prefix, or the end of the document).
+fn features_table_end_prefix(document: &mut toml_edit::DocumentMut, features_position: Option<usize>) -> String {
+    let following = features_position.and_then(|position| next_table_position(document.as_table(), position));
+    let prefix = match following.and_then(|position| table_at_position(document.as_table_mut(), position)) {
+        Some(table) => table.decor().prefix().and_then(toml_edit::RawString::as_str),
+        None => document.trailing().as_str(),
+    };
+    String::from(prefix.unwrap_or_default())
+}
+
+/// Split `text` after its begin marker line, if it has one.
+fn split_begin_marker(text: &str) -> Option<(String, String)> {
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim() == CARGO_TOML_FEATURES_BEGIN {
+            return Some((String::from(&text[.. offset]), String::from(&text[offset ..])));
+        }
+    }
+    None
+}
+
+async fn read_to_string_if_exists(path: &Utf8Path) -> Result<Option<String>, self::Error> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => Ok(Some(contents)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(self::Error::TokioFsReadToString { source }),
+    }
+}
+
+/// Render a feature's dependencies, one per line when there are several.
+fn cargo_feature_value(dependencies: &toml::Value) -> toml_edit::Value {
+    let mut array = dependencies
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(toml::Value::as_str)
+        .collect::<toml_edit::Array>();
+    if array.len() > 1 {
+        for dependency in array.iter_mut() {
+            dependency.decor_mut().set_prefix("\n    ");
+        }
+        array.set_trailing("\n");
+        array.set_trailing_comma(true);
+    }
+    toml_edit::Value::Array(array)
+}
+
+fn has_marker_line(text: &str, marker: &str) -> bool {
+    text.lines().any(|line| line.trim() == marker)
+}
+
+fn strip_marker_lines(text: &str, marker: &str) -> String {
+    if !has_marker_line(text, marker) {
+        return String::from(text);
+    }
+    text.split_inclusive('\n')
+        .filter(|line| line.trim() != marker)
+        .collect()
+}
+
+/// The position of the first table header after `position` in the document.
+fn next_table_position(table: &toml_edit::Table, position: usize) -> Option<usize> {
+    let mut next = None::<usize>;
+    for (_, item) in table {
+        let tables = match item {
+            toml_edit::Item::Table(table) => vec![table],
+            toml_edit::Item::ArrayOfTables(tables) => tables.iter().collect(),
+            _ => continue,
+        };
+        for table in tables {
+            let candidates = [
+                table.position().filter(|candidate| *candidate > position && !table.is_implicit()),
+                next_table_position(table, position),
+            ];
+            for candidate in candidates.into_iter().flatten() {
+                next = Some(next.map_or(candidate, |next| next.min(candidate)));
+            }
+        }
+    }
+    next
+}
+
+fn table_at_position(table: &mut toml_edit::Table, position: usize) -> Option<&mut toml_edit::Table> {
+    for (_, item) in table.iter_mut() {
+        let tables = match item {
+            toml_edit::Item::Table(table) => vec![table],
+            toml_edit::Item::ArrayOfTables(tables) => tables.iter_mut().collect(),
+            _ => continue,
+        };
+        for table in tables {
+            if table.position() == Some(position) && !table.is_implicit() {
+                return Some(table);
+            }
+            if let Some(table) = table_at_position(table, position) {
+                return Some(table);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{analysis, context, manifests, target},
+        Llvmup,
+    };
+
+    /// Render `build_llvmup.rs` for `platform`, with `LLVMSupport` and `LLVMDemangle` depending on each other.
+    fn render_cycle_build_llvmup(platform: ToolchainPlatform) -> String {
+        let (prefix, extension) = match platform.sys() {
+            ToolchainSys::Linux | ToolchainSys::Macos => ("lib", "a"),
+            ToolchainSys::Windows => ("", "lib"),
+        };
+        let manifests = manifests(&[
+            target(
+                "LLVMSupport",
+                "STATIC_LIBRARY",
+                r#""LLVMDemangle""#,
+                &format!("lib/{prefix}LLVMSupport.{extension}"),
+                "",
+            ),
+            target(
+                "LLVMDemangle",
+                "STATIC_LIBRARY",
+                r#""LLVMSupport""#,
+                &format!("lib/{prefix}LLVMDemangle.{extension}"),
+                "",
+            ),
+        ]);
+        let analysis = analysis(platform, manifests);
+        let llvmup = Llvmup::builder().root(Utf8Path::new("/llvmup")).build().unwrap();
+        let generator = llvmup
+            .generator(context(platform), analysis.get(), BTreeSet::new(), BTreeMap::new())
+            .unwrap();
+        generator.generate_cargo_config().unwrap().render_build_llvmup().unwrap()
+    }
+
+    #[test]
+    fn render_build_llvmup_cycle_linux() {
+        let rendered = render_cycle_build_llvmup(ToolchainPlatform::X86_64_LINUX_GNU);
+        let position = |directive: &str| {
+            rendered
+                .find(&format!("println!(\"{directive}\");"))
+                .unwrap_or_else(|| panic!("expected `{directive}` in:\n{rendered}"))
+        };
+        let start = position("cargo:rustc-link-arg=-Wl,--start-group");
+        let support = position("cargo:rustc-link-lib=static=LLVMSupport");
+        let demangle = position("cargo:rustc-link-lib=static=LLVMDemangle");
+        let end = position("cargo:rustc-link-arg=-Wl,--end-group");
+        assert!(start < support.min(demangle));
+        assert!(support.max(demangle) < end);
+    }
+
+    #[test]
+    fn render_build_llvmup_cycle_macos() {
+        let rendered = render_cycle_build_llvmup(ToolchainPlatform::X86_64_MACOS);
+        assert!(!rendered.contains("-group"), "unexpected group flags in:\n{rendered}");
+        assert!(rendered.contains("println!(\"cargo:rustc-link-lib=static=LLVMSupport\");"));
+        assert!(rendered.contains("println!(\"cargo:rustc-link-lib=static=LLVMDemangle\");"));
+    }
+
+    #[test]
+    fn render_build_llvmup_cycle_windows() {
+        let rendered = render_cycle_build_llvmup(ToolchainPlatform::X86_64_WINDOWS_MSVC);
+        assert!(!rendered.contains("-group"), "unexpected group flags in:\n{rendered}");
+        assert!(rendered.contains("println!(\"cargo:rustc-link-lib=static=LLVMSupport\");"));
+        assert!(rendered.contains("println!(\"cargo:rustc-link-lib=static=LLVMDemangle\");"));
+    }
+
+    #[tokio::test]
+    async fn check_skips_unused_files() {
+        let platform = ToolchainPlatform::X86_64_LINUX_GNU;
+        let manifests = manifests(&[target("LLVMSupport", "STATIC_LIBRARY", "", "lib/libLLVMSupport.a", "")]);
+        let analysis = analysis(platform, manifests);
+        let llvmup = Llvmup::builder().root(Utf8Path::new("/llvmup")).build().unwrap();
+        let generator = llvmup
+            .generator(context(platform), analysis.get(), BTreeSet::new(), BTreeMap::new())
+            .unwrap();
+        let config = generator.generate_cargo_config().unwrap();
+        let file_names = |drifts: Vec<GeneratedFileDrift>| {
+            drifts
+                .iter()
+                .filter_map(|drift| drift.path.file_name().map(String::from))
+                .collect::<Vec<_>>()
+        };
+
+        let dir = Utf8PathBuf::try_from(std::env::temp_dir())
+            .unwrap()
+            .join(format!("llvmup-check-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"crate\"\n")
             .await
-            .context(TokioIoWriteAllSnafu)?;
+            .unwrap();
+        assert_eq!(file_names(config.check(&dir).await.unwrap()), ["Cargo.toml"]);
 
-        Ok(())
+        config.emit(&dir).await.unwrap();
+        assert!(config.check(&dir).await.unwrap().is_empty());
+
+        tokio::fs::remove_file(dir.join(BUILD_LLVMUP_FILE_NAME)).await.unwrap();
+        tokio::fs::write(dir.join(LLVMUP_TARGETS_FILE_NAME), "").await.unwrap();
+        assert_eq!(file_names(config.check(&dir).await.unwrap()), [LLVMUP_TARGETS_FILE_NAME]);
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    /// Render the features region of `cargo_manifest` with the (dependency-less) `features` generated.
+    fn render_features(cargo_manifest: &str, features: &[&str]) -> String {
+        let platform = ToolchainPlatform::X86_64_LINUX_GNU;
+        let manifests = manifests(&[target("LLVMSupport", "STATIC_LIBRARY", "", "lib/libLLVMSupport.a", "")]);
+        let analysis = analysis(platform, manifests);
+        let llvmup = Llvmup::builder().root(Utf8Path::new("/llvmup")).build().unwrap();
+        let generator = llvmup
+            .generator(context(platform), analysis.get(), BTreeSet::new(), BTreeMap::new())
+            .unwrap();
+        let mut config = generator.generate_cargo_config().unwrap();
+        config.cargo_features = features
+            .iter()
+            .map(|feature| (String::from(*feature), toml::Value::Array(vec![])))
+            .collect();
+        config.render_cargo_features(cargo_manifest).unwrap()
+    }
+
+    #[test]
+    fn cargo_features_drop_computed_non_targets() {
+        let platform = ToolchainPlatform::X86_64_LINUX_GNU;
+        let manifests = manifests(&[
+            target(
+                "LLVMCore",
+                "STATIC_LIBRARY",
+                r#""LLVMSupport", "lib$<1:m>", "$<1:LLVM>Support""#,
+                "lib/libLLVMCore.a",
+                "",
+            ),
+            target("LLVMSupport", "STATIC_LIBRARY", "", "lib/libLLVMSupport.a", ""),
+        ]);
+        let analysis = analysis(platform, manifests);
+        let llvmup = Llvmup::builder().root(Utf8Path::new("/llvmup")).build().unwrap();
+        let generator = llvmup
+            .generator(context(platform), analysis.get(), BTreeSet::new(), BTreeMap::new())
+            .unwrap();
+        let config = generator.generate_cargo_config().unwrap();
+        let core = config.cargo_features["LLVMCore"].as_array().unwrap();
+        let core = core.iter().map(|feature| feature.as_str().unwrap()).collect::<Vec<_>>();
+        assert_eq!(core, ["LLVMSupport"]);
+        assert!(!config.cargo_features.contains_key("libm"));
+    }
+
+    #[test]
+    fn cargo_target_backend_features() {
+        let platform = ToolchainPlatform::X86_64_LINUX_GNU;
+        let manifests = manifests(&[
+            target("LLVMX86CodeGen", "STATIC_LIBRARY", r#""LLVMX86Desc""#, "lib/libLLVMX86CodeGen.a", ""),
+            target("LLVMX86Desc", "STATIC_LIBRARY", r#""LLVMX86Info""#, "lib/libLLVMX86Desc.a", ""),
+            target("LLVMX86Info", "STATIC_LIBRARY", r#""LLVMSupport""#, "lib/libLLVMX86Info.a", ""),
+            // NOTE: Without an `Info` library, `ARM` isn't a backend.
+            target("LLVMARMDesc", "STATIC_LIBRARY", r#""LLVMSupport""#, "lib/libLLVMARMDesc.a", ""),
+            target("LLVMSupport", "STATIC_LIBRARY", "", "lib/libLLVMSupport.a", ""),
+        ]);
+        let analysis = analysis(platform, manifests);
+        let llvmup = Llvmup::builder().root(Utf8Path::new("/llvmup")).build().unwrap();
+        let generator = llvmup
+            .generator(context(platform), analysis.get(), BTreeSet::new(), BTreeMap::new())
+            .unwrap();
+        let config = generator.generate_cargo_config().unwrap();
+
+        let feature = |name: &str| {
+            config.cargo_features[name]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|value| String::from(value.as_str().unwrap()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(feature("target-x86"), ["LLVMX86CodeGen", "LLVMX86Desc", "LLVMX86Info"]);
+        assert_eq!(feature("target-all"), ["target-x86"]);
+        assert!(!config.cargo_features.contains_key("target-arm"));
+
+        let rendered = config.render_llvmup_targets().unwrap();
+        assert!(rendered.contains(r#"#[cfg(feature = "LLVMX86Info")]"#));
+        assert!(rendered.contains(r#"name: "X86","#));
+        assert!(rendered.contains(r#"code_gen: cfg!(feature = "LLVMX86CodeGen"),"#));
+        assert!(rendered.contains(r#"desc: cfg!(feature = "LLVMX86Desc"),"#));
+        assert!(rendered.contains("asm_parser: false,"));
+        assert!(rendered.contains("disassembler: false,"));
+        assert!(!rendered.contains("ARM"));
+    }
+
+    #[test]
+    fn render_cargo_features_legacy_marker() {
+        let manifest = "[features]\ndefault = []\n\n#@llvmup:features\nold = []\n";
+        assert_eq!(
+            render_features(manifest, &["a", "b"]),
+            "[features]\ndefault = []\n\n#@llvmup:features\na = []\nb = []\n#@llvmup:features:end\n"
+        );
+    }
+
+    #[test]
+    fn render_cargo_features_mid_table() {
+        let manifest = "[features]\ndefault = []\n#@llvmup:features\nold = []\n#@llvmup:features:end\nuser = []\n";
+        assert_eq!(
+            render_features(manifest, &["a"]),
+            "[features]\ndefault = []\n#@llvmup:features\na = []\n#@llvmup:features:end\nuser = []\n"
+        );
+    }
+
+    #[test]
+    fn render_cargo_features_following_table() {
+        let manifest = "[features]\n#@llvmup:features\nold = []\n#@llvmup:features:end\n\n[dependencies]\nx = \"1\"\n";
+        assert_eq!(
+            render_features(manifest, &["a"]),
+            "[features]\n#@llvmup:features\na = []\n#@llvmup:features:end\n\n[dependencies]\nx = \"1\"\n"
+        );
+    }
+
+    #[test]
+    fn render_cargo_features_no_table() {
+        let manifest = "[package]\nname = \"crate\"\n";
+        assert_eq!(
+            render_features(manifest, &["a"]),
+            "[package]\nname = \"crate\"\n\n[features]\n#@llvmup:features\na = []\n#@llvmup:features:end\n"
+        );
+    }
+
+    #[test]
+    fn render_cargo_features_user_override() {
+        let manifest = "[features]\na = [\"dep\"]\n#@llvmup:features\nold = []\n#@llvmup:features:end\n";
+        assert_eq!(
+            render_features(manifest, &["a", "b"]),
+            "[features]\na = [\"dep\"]\n#@llvmup:features\nb = []\n#@llvmup:features:end\n"
+        );
+    }
+
+    #[test]
+    fn render_cargo_features_empty() {
+        let manifest = "[features]\ndefault = []\n#@llvmup:features\nold = []\n#@llvmup:features:end\nuser = []\n";
+        assert_eq!(
+            render_features(manifest, &[]),
+            "[features]\ndefault = []\n#@llvmup:features\n#@llvmup:features:end\nuser = []\n"
+        );
+
+        let manifest =
+            "[features]\ndefault = []\n\n#@llvmup:features\nold = []\n#@llvmup:features:end\n\n[dependencies]\n";
+        let emptied = render_features(manifest, &[]);
+        assert_eq!(
+            emptied,
+            "[features]\ndefault = []\n\n#@llvmup:features\n#@llvmup:features:end\n\n[dependencies]\n"
+        );
+        assert_eq!(render_features(&emptied, &[]), emptied);
+        assert_eq!(render_features(&emptied, &["old"]), manifest);
+    }
+
+    fn link_item(directive: &str) -> CargoBuildLinkItem<'static> {
+        CargoBuildLinkItem {
+            features: Vec::new(),
+            configs: BTreeMap::new(),
+            directives: vec![String::from(directive)],
+        }
+    }
+
+    fn merged_link_order(link_orders: &[Vec<usize>]) -> Result<Vec<String>, self::Error> {
+        let items = vec![link_item("x"), link_item("y"), link_item("z")];
+        let items = merge_link_orders(items, link_orders)?;
+        Ok(items.into_iter().flat_map(|item| item.directives).collect())
+    }
+
+    #[test]
+    fn merge_link_orders_kept() {
+        assert_eq!(merged_link_order(&[vec![0, 2], vec![1, 2]]).unwrap(), ["x", "y", "z"]);
+        assert_eq!(merged_link_order(&[vec![0, 1], vec![2, 0]]).unwrap(), ["z", "x", "y"]);
+        assert_eq!(merged_link_order(&[vec![0, 1, 2], vec![0, 2]]).unwrap(), ["x", "y", "z"]);
+    }
+
+    #[test]
+    fn merge_link_orders_conflict() {
+        let Err(self::Error::CargoConfigMergeLinkOrderConflict { directives }) =
+            merged_link_order(&[vec![0, 1], vec![1, 2, 0]])
+        else {
+            panic!("expected conflicting link orders to fail to merge");
+        };
+        assert_eq!(directives, ["x", "y", "z"]);
+    }
+
+    #[test]
+    fn merged_cargo_config_gates_platforms() {
+        let platforms = [ToolchainPlatform::X86_64_LINUX_GNU, ToolchainPlatform::X86_64_WINDOWS_MSVC];
+        let analyses = platforms.map(|platform| {
+            let (support, system) = match platform.sys() {
+                ToolchainSys::Windows => ("lib/LLVMSupport.lib", r#""ws2_32.lib""#),
+                _ => ("lib/libLLVMSupport.a", r#""pthread""#),
+            };
+            let manifests = manifests(&[target("LLVMSupport", "STATIC_LIBRARY", system, support, "")]);
+            (context(platform), analysis(platform, manifests))
+        });
+        let llvmup = Llvmup::builder().root(Utf8Path::new("/llvmup")).build().unwrap();
+        let config = llvmup
+            .merged_cargo_config(&analyses, &BTreeSet::new(), &BTreeMap::new())
+            .unwrap();
+        assert_eq!(config.platforms, BTreeSet::from(platforms));
+
+        let rendered = config.render_build_llvmup().unwrap().split_whitespace().collect::<String>();
+        for gated in [
+            r#"#[cfg(all(feature="pthread",all(target_os="linux",target_env="gnu")))]"#,
+            r#"#[cfg(all(feature="ws2_32",all(target_os="windows",target_env="msvc")))]"#,
+            r#"#[cfg(feature="LLVMSupport")]println!("cargo:rustc-link-lib=static=LLVMSupport");"#,
+        ] {
+            assert!(rendered.contains(gated), "expected `{gated}` in:\n{rendered}");
+        }
     }
 }
@@ -0,0 +1,366 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use quote::ToTokens;
+use rust_format::Formatter;
+use snafu::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    analysis::{closure::ToolchainLinkClosure, cxx_standard_flag},
+    toolchain::platform::ToolchainSys,
+    ToolchainConfigGenerator,
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    LlvmupAnalysisClosure { source: crate::analysis::closure::Error },
+    LlvmupAnalysisGenex { source: crate::analysis::genex::Error },
+    LlvmupGenerationCargo { source: crate::generation::cargo::Error },
+    RustFormat { source: rust_format::Error },
+    TokioFsWrite { source: tokio::io::Error },
+}
+
+impl From<self::Error> for crate::Error {
+    fn from(source: self::Error) -> Self {
+        Self::LlvmupGeneration {
+            source: crate::generation::Error::LlvmupGenerationCc { source },
+        }
+    }
+}
+
+impl From<self::Error> for crate::generation::Error {
+    fn from(source: self::Error) -> Self {
+        Self::LlvmupGenerationCc { source }
+    }
+}
+
+// NOTE: These are the definitions `llvm-config --cppflags` reports; LLVM headers expect them to be set.
+pub(super) const LLVM_COMPILE_DEFINITIONS: [&str; 3] =
+    ["__STDC_CONSTANT_MACROS", "__STDC_FORMAT_MACROS", "__STDC_LIMIT_MACROS"];
+
+impl<'a> ToolchainConfigGenerator<'a> {
+    /// Compute what is needed to compile (and link) C/C++ code against `targets` and everything they depend on.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn generate_cc_config<'t>(
+        &self,
+        targets: impl IntoIterator<Item = &'t str> + crate::LlvmupTracingDebug,
+    ) -> Result<CcConfig<'_>, self::Error> {
+        let closure = ToolchainLinkClosure::dependencies(self.toolchain_dependencies, targets)
+            .context(LlvmupAnalysisClosureSnafu)?;
+
+        let mut include_dirs = BTreeSet::new();
+        let mut system_include_dirs = BTreeSet::new();
+        let mut link_dirs = BTreeSet::new();
+        let mut defines = BTreeMap::new();
+        let mut flags = Vec::<String>::new();
+        let mut link_args = Vec::<String>::new();
+        let mut cxx_std = None::<u32>;
+        let evaluate = |values: &'a zerovec::VarZeroSlice<str>| -> Result<Vec<String>, self::Error> {
+            let mut items = vec![];
+            for value in values.iter() {
+                let evaluated = self
+                    .genex_context
+                    .evaluate_list_item(value)
+                    .context(LlvmupAnalysisGenexSnafu)?;
+                items.extend(evaluated.into_iter().map(std::borrow::Cow::into_owned));
+            }
+            Ok(items)
+        };
+        for node in closure.nodes.iter().filter(|node| node.component.is_some()) {
+            include_dirs.extend(node.interface_include_directories.iter());
+            system_include_dirs.extend(node.interface_system_include_directories.iter());
+            link_dirs.extend(node.interface_link_directories.iter());
+            for definition in evaluate(node.interface_compile_definitions)? {
+                let definition = definition.strip_prefix("-D").unwrap_or(&definition);
+                let (name, value) = match definition.split_once('=') {
+                    Some((name, value)) => (name, Some(String::from(value))),
+                    None => (definition, None),
+                };
+                defines.insert(String::from(name), value);
+            }
+            for feature in evaluate(node.interface_compile_features)? {
+                if let Some(std) = feature.strip_prefix("cxx_std_").and_then(|std| std.parse().ok()) {
+                    cxx_std = cxx_std.max(Some(std));
+                }
+            }
+            for option in evaluate(node.interface_compile_options)? {
+                push_unique(&mut flags, shell_split(&option));
+            }
+            for option in evaluate(node.interface_link_options)? {
+                push_unique(&mut link_args, self.linker_split(&option));
+            }
+        }
+        if let Some(std) = cxx_std {
+            flags.insert(0, cxx_standard_flag(self.context.platform.sys(), std));
+        }
+        if closure.nodes.iter().any(|node| node.component.is_some()) {
+            for definition in LLVM_COMPILE_DEFINITIONS {
+                defines.insert(String::from(definition), None);
+            }
+            if self.context.platform.sys() == ToolchainSys::Linux {
+                defines.insert(String::from("_GNU_SOURCE"), None);
+            }
+        }
+
+        let mut link_items = Vec::new();
+        for target_sccs in &closure.postorder_sccs {
+            let feature_gate = false;
+            self.compute_cargo_build_link_items(target_sccs, feature_gate, &mut link_items)
+                .context(LlvmupGenerationCargoSnafu)?;
+        }
+        let link_items = link_items.into_iter().map(|item| item.stmt()).collect();
+
+        Ok(CcConfig {
+            context: self.context,
+            directories: self.directories,
+            include_dirs,
+            system_include_dirs,
+            defines,
+            flags,
+            link_dirs,
+            link_args,
+            link_items,
+        })
+    }
+
+    /// Translate a `CMake` link option (which may use the `LINKER:` and `SHELL:` prefixes) into linker arguments
+    /// as passed through the compiler driver.
+    fn linker_split(&self, option: &str) -> Vec<String> {
+        if let Some(args) = option.strip_prefix("LINKER:") {
+            let args = args.strip_prefix("SHELL:").map_or_else(
+                || args.split(',').map(String::from).collect::<Vec<_>>(),
+                |args| args.split_whitespace().map(String::from).collect(),
+            );
+            match self.context.platform.sys() {
+                ToolchainSys::Linux | ToolchainSys::Macos => vec![format!("-Wl,{}", args.join(","))],
+                ToolchainSys::Windows => args,
+            }
+        } else {
+            shell_split(option)
+        }
+    }
+}
+
+fn shell_split(option: &str) -> Vec<String> {
+    match option.strip_prefix("SHELL:") {
+        Some(option) => option.split_whitespace().map(String::from).collect(),
+        None => vec![String::from(option)],
+    }
+}
+
+/// Append `items` to `list` unless it already contains them (as a contiguous run, for multi-argument options).
+fn push_unique(list: &mut Vec<String>, items: Vec<String>) {
+    if items.is_empty() || list.windows(items.len()).any(|window| window == items.as_slice()) {
+        return;
+    }
+    list.extend(items);
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct CcConfig<'a> {
+    pub context: crate::ToolchainContext,
+    pub directories: &'a crate::Directories,
+    pub include_dirs: BTreeSet<&'a str>,
+    pub system_include_dirs: BTreeSet<&'a str>,
+    pub defines: BTreeMap<String, Option<String>>,
+    /// Compiler flags from `INTERFACE_COMPILE_OPTIONS` and the C++ standard required by `INTERFACE_COMPILE_FEATURES`.
+    pub flags: Vec<String>,
+    pub link_dirs: BTreeSet<&'a str>,
+    pub link_args: Vec<String>,
+    pub link_items: Vec<syn::Stmt>,
+}
+
+impl CcConfig<'_> {
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn include_paths(&self) -> Vec<Utf8PathBuf> {
+        let root = self.directories.toolchain_root_path(self.context);
+        self.include_dirs.iter().map(|dir| root.join(dir)).collect()
+    }
+
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn system_include_paths(&self) -> Vec<Utf8PathBuf> {
+        let root = self.directories.toolchain_root_path(self.context);
+        self.system_include_dirs.iter().map(|dir| root.join(dir)).collect()
+    }
+
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn link_paths(&self) -> Vec<Utf8PathBuf> {
+        let root = self.directories.toolchain_root_path(self.context);
+        self.link_dirs.iter().map(|dir| root.join(dir)).collect()
+    }
+
+    /// Write `build_llvmup_cc.rs`, which configures a `cc::Build` and emits the matching link instructions.
+    pub async fn emit(&self, cargo_manifest_dir: &Utf8Path) -> Result<(), self::Error> {
+        let file: syn::File = {
+            let include_stmts = self.include_paths().into_iter().map(|path| -> syn::Stmt {
+                let path = path.as_str();
+                syn::parse_quote!(build.include(#path);)
+            });
+            let system_include_stmts = self.system_include_paths().into_iter().map(|path| -> syn::Stmt {
+                let path = path.as_str();
+                match self.context.platform.sys() {
+                    ToolchainSys::Linux | ToolchainSys::Macos => syn::parse_quote!(build.flag("-isystem").flag(#path);),
+                    ToolchainSys::Windows => syn::parse_quote!(build.include(#path);),
+                }
+            });
+            let define_stmts = self.defines.iter().map(|(name, value)| -> syn::Stmt {
+                let value = value.as_deref().map_or_else(
+                    || syn::parse_quote!(None),
+                    |value| -> syn::Expr { syn::parse_quote!(Some(#value)) },
+                );
+                syn::parse_quote!(build.define(#name, #value);)
+            });
+            let flag_stmts = self
+                .flags
+                .iter()
+                .map(|flag| -> syn::Stmt { syn::parse_quote!(build.flag(#flag);) });
+            let rustc_link_arg_stmts = self.link_args.iter().map(|arg| -> syn::Stmt {
+                let rustc_link_arg = format!("cargo:rustc-link-arg={arg}");
+                syn::parse_quote!(println!(#rustc_link_arg);)
+            });
+            let rustc_link_search_stmts = self.link_paths().into_iter().map(|dir| -> syn::Stmt {
+                let rustc_link_search = format!("cargo:rustc-link-search=native={dir}");
+                syn::parse_quote!(println!(#rustc_link_search);)
+            });
+            let rustc_link_lib_stmts = self.link_items.iter();
+
+            syn::parse_quote! {
+                #![allow(clippy::all)]
+                #![allow(clippy::pedantic)]
+
+                #[allow(unused)]
+                pub fn llvmup_cc_build(build: &mut cc::Build) -> &mut cc::Build {
+                    build.cpp(true);
+                    #(#include_stmts)*
+                    #(#system_include_stmts)*
+                    #(#define_stmts)*
+                    #(#flag_stmts)*
+                    build
+                }
+
+                #[allow(unused)]
+                pub fn llvmup_cc_link() {
+                    #(#rustc_link_search_stmts)*
+                    #(#rustc_link_lib_stmts)*
+                    #(#rustc_link_arg_stmts)*
+                }
+            }
+        };
+
+        let tokens = file.to_token_stream();
+        let contents = rust_format::RustFmt::default()
+            .format_tokens(tokens)
+            .context(RustFormatSnafu)?;
+
+        let cc_build_path = cargo_manifest_dir.join("build_llvmup_cc.rs");
+        tokio::fs::write(cc_build_path, contents)
+            .await
+            .context(TokioFsWriteSnafu)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "analysis"))]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{analysis, context, manifests, target},
+        Llvmup,
+        OwnedToolchainAnalysis,
+        ToolchainPlatform,
+    };
+
+    /// `LLVMCore` (linking `LLVMSupport`) with usage requirements of each kind.
+    fn usage_requirements_analysis(platform: ToolchainPlatform) -> OwnedToolchainAnalysis {
+        let manifests = manifests(&[
+            target(
+                "LLVMCore",
+                "STATIC_LIBRARY",
+                r#""LLVMSupport""#,
+                "lib/libLLVMCore.a",
+                r#""INTERFACE_COMPILE_DEFINITIONS": ["LLVM_CORE=1", "-DLLVM_CORE_FLAG"],
+                "INTERFACE_COMPILE_FEATURES": ["cxx_std_14"],
+                "INTERFACE_COMPILE_OPTIONS": ["SHELL:-Xclang -fno-rtti", "-fno-exceptions"],
+                "INTERFACE_LINK_OPTIONS": ["LINKER:--as-needed,-z,defs"]"#,
+            ),
+            target(
+                "LLVMSupport",
+                "STATIC_LIBRARY",
+                "",
+                "lib/libLLVMSupport.a",
+                r#""INTERFACE_COMPILE_FEATURES": ["cxx_std_17"],
+                "INTERFACE_COMPILE_OPTIONS": ["-fno-exceptions"],
+                "INTERFACE_INCLUDE_DIRECTORIES": ["include"]"#,
+            ),
+        ]);
+        analysis(platform, manifests)
+    }
+
+    #[test]
+    fn generate_cc_config_linux() {
+        let platform = ToolchainPlatform::X86_64_LINUX_GNU;
+        let analysis = usage_requirements_analysis(platform);
+        let llvmup = Llvmup::builder().root(Utf8Path::new("/llvmup")).build().unwrap();
+        let generator = llvmup
+            .generator(context(platform), analysis.get(), BTreeSet::new(), BTreeMap::new())
+            .unwrap();
+        let config = generator.generate_cc_config(["LLVMCore"]).unwrap();
+
+        let defines = config
+            .defines
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(defines, [
+            ("LLVM_CORE", Some("1")),
+            ("LLVM_CORE_FLAG", None),
+            ("_GNU_SOURCE", None),
+            ("__STDC_CONSTANT_MACROS", None),
+            ("__STDC_FORMAT_MACROS", None),
+            ("__STDC_LIMIT_MACROS", None),
+        ]);
+        // NOTE: The greatest standard required by any target is selected, and repeated options are only passed once.
+        assert_eq!(config.flags, ["-std=c++17", "-Xclang", "-fno-rtti", "-fno-exceptions"]);
+        assert_eq!(config.link_args, ["-Wl,--as-needed,-z,defs"]);
+        assert_eq!(config.include_dirs, BTreeSet::from(["include"]));
+    }
+
+    #[test]
+    fn generate_cc_config_windows() {
+        let platform = ToolchainPlatform::X86_64_WINDOWS_MSVC;
+        let analysis = usage_requirements_analysis(platform);
+        let llvmup = Llvmup::builder().root(Utf8Path::new("/llvmup")).build().unwrap();
+        let generator = llvmup
+            .generator(context(platform), analysis.get(), BTreeSet::new(), BTreeMap::new())
+            .unwrap();
+        let config = generator.generate_cc_config(["LLVMCore"]).unwrap();
+        assert!(!config.defines.contains_key("_GNU_SOURCE"));
+        assert_eq!(config.flags[0], "/std:c++17");
+        assert_eq!(config.link_args, ["--as-needed", "-z", "defs"]);
+    }
+
+    #[test]
+    fn linker_split() {
+        let split = |platform: ToolchainPlatform, option: &str| {
+            let analysis = usage_requirements_analysis(platform);
+            let llvmup = Llvmup::builder().root(Utf8Path::new("/llvmup")).build().unwrap();
+            let generator = llvmup
+                .generator(context(platform), analysis.get(), BTreeSet::new(), BTreeMap::new())
+                .unwrap();
+            generator.linker_split(option)
+        };
+        let linux = ToolchainPlatform::X86_64_LINUX_GNU;
+        let macos = ToolchainPlatform::ARM64_MACOS;
+        let windows = ToolchainPlatform::X86_64_WINDOWS_MSVC;
+        assert_eq!(split(linux, "LINKER:-z,now"), ["-Wl,-z,now"]);
+        assert_eq!(split(linux, "LINKER:SHELL:-z now"), ["-Wl,-z,now"]);
+        assert_eq!(split(macos, "LINKER:-dead_strip"), ["-Wl,-dead_strip"]);
+        assert_eq!(split(windows, "LINKER:/DEBUG,/OPT:REF"), ["/DEBUG", "/OPT:REF"]);
+        assert_eq!(split(windows, "LINKER:SHELL:/DEBUG /OPT:REF"), ["/DEBUG", "/OPT:REF"]);
+        assert_eq!(split(linux, "SHELL:-framework Foundation"), ["-framework", "Foundation"]);
+        assert_eq!(split(linux, "-pthread"), ["-pthread"]);
+    }
+}
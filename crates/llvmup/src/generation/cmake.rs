@@ -0,0 +1,214 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use core::fmt::Write;
+use snafu::prelude::*;
+use std::collections::BTreeSet;
+
+use crate::{toolchain::platform::ToolchainSys, ToolchainComponent, ToolchainContext};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    TokioFsCreateDirAll { source: tokio::io::Error },
+    TokioFsWrite { source: tokio::io::Error },
+}
+
+impl From<self::Error> for crate::Error {
+    fn from(source: self::Error) -> Self {
+        Self::LlvmupGeneration {
+            source: crate::generation::Error::LlvmupGenerationCMake { source },
+        }
+    }
+}
+
+impl From<self::Error> for crate::generation::Error {
+    fn from(source: self::Error) -> Self {
+        Self::LlvmupGenerationCMake { source }
+    }
+}
+
+/// The package configurations each component provides, by `find_package` name and location (relative to the
+/// toolchain root).
+const CMAKE_PACKAGES: [(ToolchainComponent, &str, &str); 3] = [
+    (ToolchainComponent::Llvm, "LLVM", "lib/cmake/llvm"),
+    (ToolchainComponent::Clang, "Clang", "lib/cmake/clang"),
+    (ToolchainComponent::Mlir, "MLIR", "lib/cmake/mlir"),
+];
+
+/// A `CMake` toolchain file, which points `find_package` at a toolchain tree, and compiles and links with its tools.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct CMakeToolchainFile<'a> {
+    pub context: ToolchainContext,
+    pub directories: &'a crate::Directories,
+    pub components: BTreeSet<ToolchainComponent>,
+}
+
+impl CMakeToolchainFile<'_> {
+    /// The conventional location of the toolchain file within the toolchain tree.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn path(&self) -> Utf8PathBuf {
+        self.directories
+            .toolchain_root_path(self.context)
+            .join("share")
+            .join("llvmup")
+            .join("toolchain.cmake")
+    }
+
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn render(&self) -> String {
+        let root = self.directories.toolchain_root_path(self.context);
+        let sys = self.context.platform.sys();
+        let exe = if sys == ToolchainSys::Windows { ".exe" } else { "" };
+
+        let mut cmake = String::from("# Generated by llvmup. Do not edit.\n\n");
+        writeln!(cmake, "set(LLVMUP_TOOLCHAIN_ROOT \"{root}\")").unwrap();
+        cmake.push_str("list(PREPEND CMAKE_PREFIX_PATH \"${LLVMUP_TOOLCHAIN_ROOT}\")\n");
+        for (component, package, dir) in CMAKE_PACKAGES {
+            if self.components.contains(&component) {
+                writeln!(cmake, "set({package}_DIR \"${{LLVMUP_TOOLCHAIN_ROOT}}/{dir}\")").unwrap();
+            }
+        }
+
+        let clang = self.components.contains(&ToolchainComponent::ToolClang);
+        if clang {
+            let (cc, cxx) = match sys {
+                ToolchainSys::Linux | ToolchainSys::Macos => ("clang", "clang++"),
+                ToolchainSys::Windows => ("clang-cl", "clang-cl"),
+            };
+            cmake.push('\n');
+            writeln!(cmake, "set(CMAKE_C_COMPILER \"${{LLVMUP_TOOLCHAIN_ROOT}}/bin/{cc}{exe}\")").unwrap();
+            writeln!(cmake, "set(CMAKE_CXX_COMPILER \"${{LLVMUP_TOOLCHAIN_ROOT}}/bin/{cxx}{exe}\")").unwrap();
+        }
+
+        // NOTE: `mold` is preferred when both linkers are installed, since it can only have been asked for explicitly.
+        let mold = self.components.iter().find_map(|component| match component {
+            ToolchainComponent::ToolMold { platform, release } if sys == ToolchainSys::Linux => {
+                Some(self.directories.mold_root_path(*platform, *release).join("bin").join("mold"))
+            },
+            _ => None,
+        });
+        let lld = self.components.contains(&ToolchainComponent::ToolLld).then(|| {
+            let lld = match sys {
+                ToolchainSys::Linux => "ld.lld",
+                ToolchainSys::Macos => "ld64.lld",
+                ToolchainSys::Windows => "lld-link",
+            };
+            root.join("bin").join(format!("{lld}{exe}"))
+        });
+        let linker = mold.map(|path| ("MOLD", path)).or(lld.map(|path| ("LLD", path)));
+        if let Some((linker_type, path)) = linker {
+            cmake.push('\n');
+            writeln!(cmake, "set(CMAKE_LINKER_TYPE {linker_type})").unwrap();
+            writeln!(cmake, "list(PREPEND CMAKE_PROGRAM_PATH \"{}\")", path.parent().unwrap_or(&root)).unwrap();
+            // NOTE: Clang can be given the exact linker to use, rather than searching `PATH` for it.
+            if clang && sys != ToolchainSys::Windows {
+                for lang in ["C", "CXX"] {
+                    writeln!(cmake, "set(CMAKE_{lang}_USING_LINKER_{linker_type} \"--ld-path={path}\")").unwrap();
+                }
+            }
+        }
+        cmake
+    }
+
+    /// Write the toolchain file to `path` (e.g., [`CMakeToolchainFile::path`]), for use with
+    /// `-DCMAKE_TOOLCHAIN_FILE`.
+    pub async fn emit(&self, path: &Utf8Path) -> Result<(), self::Error> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .context(TokioFsCreateDirAllSnafu)?;
+        }
+        tokio::fs::write(path, self.render())
+            .await
+            .context(TokioFsWriteSnafu)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Directories, ToolchainPlatform, ToolchainRelease, ToolchainRevision, ToolchainVariant};
+
+    fn render(platform: ToolchainPlatform, components: &[ToolchainComponent]) -> String {
+        let directories = Directories::new(Some(Utf8Path::new("/llvmup"))).unwrap();
+        let context = ToolchainContext::new(
+            ToolchainVariant::Llvmorg,
+            ToolchainRelease::new(17, 0, Some(6)),
+            ToolchainRevision::new(None),
+            platform,
+        );
+        let file = CMakeToolchainFile {
+            context,
+            directories: &directories,
+            components: components.iter().copied().collect(),
+        };
+        file.render()
+    }
+
+    #[test]
+    fn render_linux_mold() {
+        let platform = ToolchainPlatform::X86_64_LINUX_GNU;
+        let mold = ToolchainComponent::ToolMold {
+            platform,
+            release: ToolchainRelease::new(2, 34, Some(1)),
+        };
+        let cmake = render(platform, &[
+            ToolchainComponent::Llvm,
+            ToolchainComponent::Clang,
+            ToolchainComponent::ToolClang,
+            ToolchainComponent::ToolLld,
+            mold,
+        ]);
+        assert!(cmake.contains("set(LLVMUP_TOOLCHAIN_ROOT \"/llvmup/trees/llvmorg-17.0.6/x86_64-linux-gnu\")\n"));
+        assert!(cmake.contains("set(LLVM_DIR \"${LLVMUP_TOOLCHAIN_ROOT}/lib/cmake/llvm\")\n"));
+        assert!(cmake.contains("set(Clang_DIR \"${LLVMUP_TOOLCHAIN_ROOT}/lib/cmake/clang\")\n"));
+        assert!(!cmake.contains("MLIR_DIR"));
+        assert!(cmake.contains("set(CMAKE_C_COMPILER \"${LLVMUP_TOOLCHAIN_ROOT}/bin/clang\")\n"));
+        assert!(cmake.contains("set(CMAKE_CXX_COMPILER \"${LLVMUP_TOOLCHAIN_ROOT}/bin/clang++\")\n"));
+        assert!(cmake.contains("set(CMAKE_LINKER_TYPE MOLD)\n"));
+        let mold_bin = "/llvmup/trees/mold-2.34.1/x86_64-linux-gnu/bin";
+        assert!(cmake.contains(&format!("list(PREPEND CMAKE_PROGRAM_PATH \"{mold_bin}\")\n")));
+        for lang in ["C", "CXX"] {
+            assert!(cmake.contains(&format!("set(CMAKE_{lang}_USING_LINKER_MOLD \"--ld-path={mold_bin}/mold\")\n")));
+        }
+        assert!(!cmake.contains("LLD"));
+    }
+
+    #[test]
+    fn render_macos_lld() {
+        let cmake = render(ToolchainPlatform::ARM64_MACOS, &[
+            ToolchainComponent::Llvm,
+            ToolchainComponent::ToolClang,
+            ToolchainComponent::ToolLld,
+        ]);
+        assert!(cmake.contains("set(LLVM_DIR \"${LLVMUP_TOOLCHAIN_ROOT}/lib/cmake/llvm\")\n"));
+        assert!(!cmake.contains("Clang_DIR"));
+        assert!(cmake.contains("set(CMAKE_C_COMPILER \"${LLVMUP_TOOLCHAIN_ROOT}/bin/clang\")\n"));
+        assert!(cmake.contains("set(CMAKE_LINKER_TYPE LLD)\n"));
+        assert!(cmake.contains(
+            "set(CMAKE_CXX_USING_LINKER_LLD \"--ld-path=/llvmup/trees/llvmorg-17.0.6/arm64-macos/bin/ld64.lld\")\n"
+        ));
+    }
+
+    #[test]
+    fn render_windows() {
+        let cmake = render(ToolchainPlatform::X86_64_WINDOWS_MSVC, &[
+            ToolchainComponent::Llvm,
+            ToolchainComponent::ToolClang,
+            ToolchainComponent::ToolLld,
+        ]);
+        assert!(cmake.contains("set(CMAKE_C_COMPILER \"${LLVMUP_TOOLCHAIN_ROOT}/bin/clang-cl.exe\")\n"));
+        assert!(cmake.contains("set(CMAKE_CXX_COMPILER \"${LLVMUP_TOOLCHAIN_ROOT}/bin/clang-cl.exe\")\n"));
+        assert!(cmake.contains("set(CMAKE_LINKER_TYPE LLD)\n"));
+        assert!(!cmake.contains("--ld-path"));
+    }
+
+    #[test]
+    fn render_without_tools() {
+        let cmake = render(ToolchainPlatform::X86_64_LINUX_GNU, &[ToolchainComponent::Llvm]);
+        assert!(cmake.contains("set(LLVM_DIR \"${LLVMUP_TOOLCHAIN_ROOT}/lib/cmake/llvm\")\n"));
+        assert!(!cmake.contains("CMAKE_C_COMPILER"));
+        assert!(!cmake.contains("CMAKE_LINKER_TYPE"));
+    }
+}
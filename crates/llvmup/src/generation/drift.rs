@@ -0,0 +1,282 @@
+use camino::Utf8PathBuf;
+use core::fmt::Write;
+
+/// The number of unchanged lines shown around each change.
+const DRIFT_CONTEXT_LINES: usize = 3;
+
+/// A line of a [`GeneratedFileDriftHunk`] (without its line terminator).
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, Eq, PartialEq)]
+pub enum GeneratedFileDriftLine {
+    /// A line both on disk and in the generated contents.
+    Unchanged(String),
+    /// A line on disk which generation would remove.
+    Removed(String),
+    /// A line which generation would add.
+    Added(String),
+}
+
+/// A run of changes (with surrounding context), starting at the given 1-based lines on disk and in the generated
+/// contents.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct GeneratedFileDriftHunk {
+    pub actual_start: usize,
+    pub expected_start: usize,
+    pub lines: Vec<GeneratedFileDriftLine>,
+}
+
+/// A generated file whose contents on disk differ from those generation would write.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct GeneratedFileDrift {
+    pub path: Utf8PathBuf,
+    /// The contents on disk, unless the file is missing.
+    pub actual: Option<String>,
+    pub expected: String,
+}
+
+impl GeneratedFileDrift {
+    /// The drift of `path` from `expected`, if any.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn new(path: Utf8PathBuf, actual: Option<String>, expected: String) -> Option<Self> {
+        if actual.as_ref() == Some(&expected) {
+            return None;
+        }
+        Some(Self { path, actual, expected })
+    }
+
+    /// The changes from the contents on disk to the generated contents, line by line.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn hunks(&self) -> Vec<GeneratedFileDriftHunk> {
+        let actual = self
+            .actual
+            .as_deref()
+            .map(|actual| actual.split_inclusive('\n').collect::<Vec<_>>())
+            .unwrap_or_default();
+        let expected = self.expected.split_inclusive('\n').collect::<Vec<_>>();
+        let lines = diff_lines(&actual, &expected);
+
+        // NOTE: Changes closer than twice the context share a hunk, so that no context line is shown twice.
+        let mut ranges = Vec::<core::ops::Range<usize>>::new();
+        for (index, line) in lines.iter().enumerate() {
+            if matches!(line, GeneratedFileDriftLine::Unchanged(_)) {
+                continue;
+            }
+            let start = index.saturating_sub(DRIFT_CONTEXT_LINES);
+            let end = (index + 1 + DRIFT_CONTEXT_LINES).min(lines.len());
+            match ranges.last_mut() {
+                Some(range) if range.end >= start => range.end = end,
+                _ => ranges.push(start .. end),
+            }
+        }
+
+        let mut hunks = Vec::with_capacity(ranges.len());
+        let (mut actual_line, mut expected_line, mut index) = (1, 1, 0);
+        for range in ranges {
+            for line in &lines[index .. range.start] {
+                advance(line, &mut actual_line, &mut expected_line);
+            }
+            let (actual_start, expected_start) = (actual_line, expected_line);
+            for line in &lines[range.clone()] {
+                advance(line, &mut actual_line, &mut expected_line);
+            }
+            index = range.end;
+            hunks.push(GeneratedFileDriftHunk {
+                actual_start,
+                expected_start,
+                lines: lines[range].to_vec(),
+            });
+        }
+        hunks
+    }
+
+    /// Render the drift as a unified diff (e.g., for CI logs).
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn unified_diff(&self) -> String {
+        let mut diff = String::new();
+        if self.actual.is_some() {
+            writeln!(diff, "--- {}", self.path).unwrap();
+        } else {
+            writeln!(diff, "--- /dev/null").unwrap();
+        }
+        writeln!(diff, "+++ {}", self.path).unwrap();
+        for hunk in self.hunks() {
+            let actual_len = hunk
+                .lines
+                .iter()
+                .filter(|line| !matches!(line, GeneratedFileDriftLine::Added(_)))
+                .count();
+            let expected_len = hunk
+                .lines
+                .iter()
+                .filter(|line| !matches!(line, GeneratedFileDriftLine::Removed(_)))
+                .count();
+            // NOTE: An empty range is numbered after the line it follows, as in `diff -u`.
+            let actual_start = if actual_len == 0 { hunk.actual_start - 1 } else { hunk.actual_start };
+            let expected_start = if expected_len == 0 { hunk.expected_start - 1 } else { hunk.expected_start };
+            writeln!(diff, "@@ -{actual_start},{actual_len} +{expected_start},{expected_len} @@").unwrap();
+            for line in &hunk.lines {
+                let (sign, text) = match line {
+                    GeneratedFileDriftLine::Unchanged(text) => (' ', text),
+                    GeneratedFileDriftLine::Removed(text) => ('-', text),
+                    GeneratedFileDriftLine::Added(text) => ('+', text),
+                };
+                writeln!(diff, "{sign}{text}").unwrap();
+            }
+        }
+        diff
+    }
+}
+
+fn advance(line: &GeneratedFileDriftLine, actual_line: &mut usize, expected_line: &mut usize) {
+    match line {
+        GeneratedFileDriftLine::Unchanged(_) => {
+            *actual_line += 1;
+            *expected_line += 1;
+        },
+        GeneratedFileDriftLine::Removed(_) => *actual_line += 1,
+        GeneratedFileDriftLine::Added(_) => *expected_line += 1,
+    }
+}
+
+/// Diff `actual` and `expected` (lines including their terminators) by longest common subsequence.
+fn diff_lines(actual: &[&str], expected: &[&str]) -> Vec<GeneratedFileDriftLine> {
+    let line = |text: &str| String::from(text.strip_suffix('\n').unwrap_or(text));
+
+    // NOTE: Regeneration usually touches a small part of a file, so the common prefix and suffix are set aside to
+    // keep the table small.
+    let prefix = actual
+        .iter()
+        .zip(expected)
+        .take_while(|(actual, expected)| actual == expected)
+        .count();
+    let suffix = actual[prefix ..]
+        .iter()
+        .rev()
+        .zip(expected[prefix ..].iter().rev())
+        .take_while(|(actual, expected)| actual == expected)
+        .count();
+    let actual_middle = &actual[prefix .. actual.len() - suffix];
+    let expected_middle = &expected[prefix .. expected.len() - suffix];
+
+    // NOTE: `lengths[i * width + j]` is the length of the longest common subsequence of `actual_middle[i ..]` and
+    // `expected_middle[j ..]`.
+    let width = expected_middle.len() + 1;
+    let mut lengths = vec![0_usize; (actual_middle.len() + 1) * width];
+    for i in (0 .. actual_middle.len()).rev() {
+        for j in (0 .. expected_middle.len()).rev() {
+            lengths[i * width + j] = if actual_middle[i] == expected_middle[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines = actual[.. prefix]
+        .iter()
+        .map(|text| GeneratedFileDriftLine::Unchanged(line(text)))
+        .collect::<Vec<_>>();
+    let (mut i, mut j) = (0, 0);
+    while i < actual_middle.len() && j < expected_middle.len() {
+        if actual_middle[i] == expected_middle[j] {
+            lines.push(GeneratedFileDriftLine::Unchanged(line(actual_middle[i])));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            lines.push(GeneratedFileDriftLine::Removed(line(actual_middle[i])));
+            i += 1;
+        } else {
+            lines.push(GeneratedFileDriftLine::Added(line(expected_middle[j])));
+            j += 1;
+        }
+    }
+    lines.extend(
+        actual_middle[i ..]
+            .iter()
+            .map(|text| GeneratedFileDriftLine::Removed(line(text))),
+    );
+    lines.extend(
+        expected_middle[j ..]
+            .iter()
+            .map(|text| GeneratedFileDriftLine::Added(line(text))),
+    );
+    lines.extend(
+        actual[actual.len() - suffix ..]
+            .iter()
+            .map(|text| GeneratedFileDriftLine::Unchanged(line(text))),
+    );
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drift(actual: Option<&str>, expected: &str) -> GeneratedFileDrift {
+        GeneratedFileDrift::new(Utf8PathBuf::from("build_llvmup.rs"), actual.map(String::from), String::from(expected))
+            .unwrap()
+    }
+
+    #[test]
+    fn diff_lines_replacement() {
+        let lines = diff_lines(&["a\n", "b\n", "c\n", "d\n"], &["a\n", "B\n", "c\n", "d\n", "e\n"]);
+        assert!(
+            lines
+                == [
+                    GeneratedFileDriftLine::Unchanged(String::from("a")),
+                    GeneratedFileDriftLine::Removed(String::from("b")),
+                    GeneratedFileDriftLine::Added(String::from("B")),
+                    GeneratedFileDriftLine::Unchanged(String::from("c")),
+                    GeneratedFileDriftLine::Unchanged(String::from("d")),
+                    GeneratedFileDriftLine::Added(String::from("e")),
+                ]
+        );
+        assert!(diff_lines(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn no_drift() {
+        let path = Utf8PathBuf::from("build_llvmup.rs");
+        assert!(GeneratedFileDrift::new(path, Some(String::from("a\n")), String::from("a\n")).is_none());
+    }
+
+    #[test]
+    fn hunks_split_distant_changes() {
+        let actual = (1 ..= 20).fold(String::new(), |mut actual, line| {
+            writeln!(actual, "{line}").unwrap();
+            actual
+        });
+        let expected = actual.replace("\n2\n", "\ntwo\n").replace("\n18\n", "\neighteen\n");
+        let hunks = drift(Some(&actual), &expected).hunks();
+        assert_eq!(hunks.len(), 2);
+        assert_eq!((hunks[0].actual_start, hunks[0].expected_start), (1, 1));
+        assert_eq!(hunks[0].lines.len(), 6);
+        assert_eq!((hunks[1].actual_start, hunks[1].expected_start), (15, 15));
+        assert_eq!(hunks[1].lines.len(), 7);
+
+        // NOTE: Changes sharing context lines share a hunk.
+        let expected = actual.replace("\n2\n", "\ntwo\n").replace("\n8\n", "\neight\n");
+        assert_eq!(drift(Some(&actual), &expected).hunks().len(), 1);
+    }
+
+    #[test]
+    fn unified_diff_changed() {
+        let diff = drift(Some("a\nb\nc\n"), "a\nB\nc\n").unified_diff();
+        assert_eq!(diff, "--- build_llvmup.rs\n+++ build_llvmup.rs\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n");
+    }
+
+    #[test]
+    fn unified_diff_missing() {
+        let diff = drift(None, "a\nb\n").unified_diff();
+        assert_eq!(diff, "--- /dev/null\n+++ build_llvmup.rs\n@@ -0,0 +1,2 @@\n+a\n+b\n");
+    }
+
+    #[test]
+    fn unified_diff_emptied() {
+        let diff = drift(Some("a\n"), "").unified_diff();
+        assert_eq!(diff, "--- build_llvmup.rs\n+++ build_llvmup.rs\n@@ -1,1 +0,0 @@\n-a\n");
+    }
+}
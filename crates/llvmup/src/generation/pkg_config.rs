@@ -0,0 +1,416 @@
+use camino::{Utf8Path, Utf8PathBuf};
+use core::fmt::Write;
+use snafu::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    analysis::{ToolchainComponentDependencyNode, ToolchainComponentDependencyNodeKind},
+    toolchain::platform::ToolchainSys,
+    ToolchainComponent,
+    ToolchainConfigGenerator,
+};
+
+#[derive(Debug, Snafu)]
+pub enum Error {
+    LlvmupAnalysisGenex { source: crate::analysis::genex::Error },
+    TokioFsCreateDirAll { source: tokio::io::Error },
+    TokioFsWrite { source: tokio::io::Error },
+}
+
+impl From<self::Error> for crate::Error {
+    fn from(source: self::Error) -> Self {
+        Self::LlvmupGeneration {
+            source: crate::generation::Error::LlvmupGenerationPkgConfig { source },
+        }
+    }
+}
+
+impl From<self::Error> for crate::generation::Error {
+    fn from(source: self::Error) -> Self {
+        Self::LlvmupGenerationPkgConfig { source }
+    }
+}
+
+/// Whether pkg-config packages are generated for each library target (e.g., `LLVMCore`) or for each component
+/// (e.g., `llvm`).
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum PkgConfigGranularity {
+    Target,
+    Component,
+}
+
+/// The contents of a `.pc` file.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct PkgConfigPackage {
+    pub name: String,
+    pub description: String,
+    pub cflags: Vec<String>,
+    pub libs: Vec<String>,
+    pub libs_private: Vec<String>,
+    pub requires: BTreeSet<String>,
+}
+
+impl<'a> ToolchainConfigGenerator<'a> {
+    /// Compute a pkg-config package for each library target (or component), whose dependencies on other targets
+    /// (or components) become `Requires`, and on system libraries `Libs.private`.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn generate_pkg_config(&self, granularity: PkgConfigGranularity) -> Result<PkgConfig<'_>, self::Error> {
+        let postorder_sccs = self.selected_postorder_sccs();
+        let mut packages = Vec::new();
+        match granularity {
+            PkgConfigGranularity::Target => {
+                // NOTE: pkg-config can't resolve cyclic `Requires`, so the members of a dependency cycle all link the
+                // whole cycle instead of requiring each other.
+                for scc in postorder_sccs.iter().rev() {
+                    for node in scc.iter().filter(|node| Self::pkg_config_packaged(node)) {
+                        let package = self.pkg_config_package(
+                            Self::pkg_config_target_package_name(node.name),
+                            format!("The {} library", node.name),
+                            std::slice::from_ref(node),
+                            scc,
+                            |dependency| {
+                                (!scc.contains(dependency) && Self::pkg_config_packaged(dependency))
+                                    .then(|| Self::pkg_config_target_package_name(dependency.name))
+                            },
+                        )?;
+                        packages.push(package);
+                    }
+                }
+            },
+            PkgConfigGranularity::Component => {
+                let mut components = BTreeMap::<ToolchainComponent, Vec<ToolchainComponentDependencyNode<'a>>>::new();
+                for node in postorder_sccs.iter().rev().flatten() {
+                    if let Some(component) = node.component.filter(|_| Self::pkg_config_packaged(node)) {
+                        components.entry(component).or_default().push(*node);
+                    }
+                }
+                for (component, nodes) in &components {
+                    let package = self.pkg_config_package(
+                        component.to_string(),
+                        format!("The {component} libraries"),
+                        nodes,
+                        nodes,
+                        |dependency| {
+                            dependency
+                                .component
+                                .filter(|dependency| dependency != component)
+                                .map(|dependency| dependency.to_string())
+                        },
+                    )?;
+                    packages.push(package);
+                }
+            },
+        }
+        Ok(PkgConfig {
+            context: self.context,
+            directories: self.directories,
+            packages,
+        })
+    }
+
+    /// Whether a package is generated for `node` (i.e., it is a component library).
+    fn pkg_config_packaged(node: &ToolchainComponentDependencyNode<'_>) -> bool {
+        node.component.is_some()
+            && !matches!(
+                node.kind,
+                ToolchainComponentDependencyNodeKind::Executable | ToolchainComponentDependencyNodeKind::Module
+            )
+    }
+
+    fn pkg_config_target_package_name(name: &str) -> String {
+        name.replace("::", "-")
+    }
+
+    /// Compute a package providing the usage requirements of `nodes`, which links `linked` (in link order), and
+    /// requires the packages `requires` maps their other dependencies to.
+    fn pkg_config_package(
+        &self,
+        name: String,
+        description: String,
+        nodes: &[ToolchainComponentDependencyNode<'a>],
+        linked: &[ToolchainComponentDependencyNode<'a>],
+        requires: impl Fn(&ToolchainComponentDependencyNode<'a>) -> Option<String>,
+    ) -> Result<PkgConfigPackage, self::Error> {
+        let sys = self.context.platform.sys();
+        let prefixed = |dir: &str| {
+            if Utf8Path::new(dir).is_absolute() {
+                String::from(dir)
+            } else {
+                format!("${{prefix}}/{dir}")
+            }
+        };
+        let evaluate = |values: &'a zerovec::VarZeroSlice<str>| -> Result<Vec<String>, self::Error> {
+            let mut items = vec![];
+            for value in values.iter() {
+                let evaluated = self
+                    .genex_context
+                    .evaluate_list_item(value)
+                    .context(LlvmupAnalysisGenexSnafu)?;
+                items.extend(evaluated.into_iter().map(std::borrow::Cow::into_owned));
+            }
+            Ok(items)
+        };
+        let mut cflags = Vec::<String>::new();
+        for node in nodes {
+            for dir in node.interface_include_directories.iter() {
+                push_unique(&mut cflags, format!("-I{}", prefixed(dir)));
+            }
+            for dir in node.interface_system_include_directories.iter() {
+                push_unique(&mut cflags, format!("-isystem {}", prefixed(dir)));
+            }
+            for definition in evaluate(node.interface_compile_definitions)? {
+                let definition = definition.strip_prefix("-D").unwrap_or(&definition);
+                push_unique(&mut cflags, format!("-D{definition}"));
+            }
+            for option in evaluate(node.interface_compile_options)? {
+                push_unique(&mut cflags, option);
+            }
+        }
+
+        let mut libs = Vec::<String>::new();
+        let mut lib_names = Vec::<String>::new();
+        for node in linked {
+            for dir in node.interface_link_directories.iter() {
+                push_unique(&mut libs, format!("-L{}", prefixed(dir)));
+            }
+            for object in node.objects.iter() {
+                lib_names.push(prefixed(object));
+            }
+            let Some(location) = node.link_location(self.context.platform).map(Utf8Path::new) else {
+                continue;
+            };
+            if let Some(parent) = location.parent().filter(|parent| !parent.as_str().is_empty()) {
+                push_unique(&mut libs, format!("-L{}", prefixed(parent.as_str())));
+            }
+            let file_name = location.file_name().unwrap_or(node.name);
+            match sys {
+                ToolchainSys::Linux | ToolchainSys::Macos => {
+                    let stem = location.file_stem().unwrap_or(node.name);
+                    lib_names.push(format!("-l{}", stem.strip_prefix("lib").unwrap_or(stem)));
+                },
+                ToolchainSys::Windows => lib_names.push(String::from(file_name)),
+            }
+        }
+        if linked.len() > 1 && sys == ToolchainSys::Linux {
+            lib_names.insert(0, String::from("-Wl,--start-group"));
+            lib_names.push(String::from("-Wl,--end-group"));
+        }
+        libs.extend(lib_names);
+
+        let mut libs_private = Vec::<String>::new();
+        let mut requires_packages = BTreeSet::new();
+        for node in nodes.iter().chain(linked) {
+            for option in evaluate(node.interface_link_options)? {
+                push_unique(&mut libs_private, option);
+            }
+            for dependency in self.toolchain_dependencies.neighbors(*node) {
+                if let Some(package) = requires(&dependency) {
+                    requires_packages.insert(package);
+                } else if dependency.component.is_none() {
+                    push_unique(&mut libs_private, system_library_flag(sys, dependency.name));
+                }
+            }
+        }
+        requires_packages.remove(&name);
+
+        Ok(PkgConfigPackage {
+            name,
+            description,
+            cflags,
+            libs,
+            libs_private,
+            requires: requires_packages,
+        })
+    }
+}
+
+fn push_unique(flags: &mut Vec<String>, flag: String) {
+    if !flags.contains(&flag) {
+        flags.push(flag);
+    }
+}
+
+/// Render a system library (e.g., `m`) as a linker flag, passing flags (e.g., `-lpthread`) and paths through.
+fn system_library_flag(sys: ToolchainSys, lib: &str) -> String {
+    if lib.starts_with('-') || lib.contains(['/', '\\']) {
+        return String::from(lib);
+    }
+    match sys {
+        ToolchainSys::Linux | ToolchainSys::Macos => format!("-l{lib}"),
+        ToolchainSys::Windows => format!("{lib}.lib"),
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct PkgConfig<'a> {
+    pub context: crate::ToolchainContext,
+    pub directories: &'a crate::Directories,
+    pub packages: Vec<PkgConfigPackage>,
+}
+
+impl PkgConfig<'_> {
+    /// The directory of the toolchain tree the packages are conventionally installed in (i.e., `lib/pkgconfig`), for
+    /// use in `PKG_CONFIG_PATH`.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn pkg_config_dir(&self) -> Utf8PathBuf {
+        self.directories
+            .toolchain_root_path(self.context)
+            .join("lib")
+            .join("pkgconfig")
+    }
+
+    /// Render the `.pc` file of `package`.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn render(&self, package: &PkgConfigPackage) -> String {
+        let root = self.directories.toolchain_root_path(self.context);
+        let mut pc = String::new();
+        writeln!(pc, "prefix={root}").unwrap();
+        writeln!(pc, "libdir=${{prefix}}/lib").unwrap();
+        writeln!(pc, "includedir=${{prefix}}/include").unwrap();
+        writeln!(pc).unwrap();
+        writeln!(pc, "Name: {}", package.name).unwrap();
+        writeln!(pc, "Description: {}", package.description).unwrap();
+        writeln!(pc, "Version: {}", self.context.release).unwrap();
+        if !package.requires.is_empty() {
+            let requires = package.requires.iter().map(String::as_str).collect::<Vec<_>>();
+            writeln!(pc, "Requires: {}", requires.join(", ")).unwrap();
+        }
+        if !package.cflags.is_empty() {
+            writeln!(pc, "Cflags: {}", package.cflags.join(" ")).unwrap();
+        }
+        if !package.libs.is_empty() {
+            writeln!(pc, "Libs: {}", package.libs.join(" ")).unwrap();
+        }
+        if !package.libs_private.is_empty() {
+            writeln!(pc, "Libs.private: {}", package.libs_private.join(" ")).unwrap();
+        }
+        pc
+    }
+
+    /// Write a `<name>.pc` file for each package to `pkg_config_dir` (e.g., [`PkgConfig::pkg_config_dir`]).
+    pub async fn emit(&self, pkg_config_dir: &Utf8Path) -> Result<(), self::Error> {
+        tokio::fs::create_dir_all(pkg_config_dir)
+            .await
+            .context(TokioFsCreateDirAllSnafu)?;
+        for package in &self.packages {
+            let path = pkg_config_dir.join(format!("{}.pc", package.name));
+            tokio::fs::write(path, self.render(package))
+                .await
+                .context(TokioFsWriteSnafu)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "analysis"))]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{analysis, context, manifests, target},
+        Llvmup,
+        ToolchainPlatform,
+    };
+
+    /// Render the packages for `platform`, where `LLVMCore` links `LLVMSupport` (in a cycle with `LLVMDemangle`) and
+    /// the system library `pthread`.
+    fn render_pkg_config(platform: ToolchainPlatform, granularity: PkgConfigGranularity) -> BTreeMap<String, String> {
+        let (prefix, extension) = match platform.sys() {
+            ToolchainSys::Linux | ToolchainSys::Macos => ("lib", "a"),
+            ToolchainSys::Windows => ("", "lib"),
+        };
+        let location = |name: &str| format!("lib/{prefix}{name}.{extension}");
+        let manifests = manifests(&[
+            target(
+                "LLVMCore",
+                "STATIC_LIBRARY",
+                r#""LLVMSupport", "pthread""#,
+                &location("LLVMCore"),
+                r#""INTERFACE_INCLUDE_DIRECTORIES": ["include"]"#,
+            ),
+            target("LLVMSupport", "STATIC_LIBRARY", r#""LLVMDemangle""#, &location("LLVMSupport"), ""),
+            target("LLVMDemangle", "STATIC_LIBRARY", r#""LLVMSupport""#, &location("LLVMDemangle"), ""),
+        ]);
+        let analysis = analysis(platform, manifests);
+        let llvmup = Llvmup::builder().root(Utf8Path::new("/llvmup")).build().unwrap();
+        let generator = llvmup
+            .generator(context(platform), analysis.get(), BTreeSet::new(), BTreeMap::new())
+            .unwrap();
+        let pkg_config = generator.generate_pkg_config(granularity).unwrap();
+        pkg_config
+            .packages
+            .iter()
+            .map(|package| (package.name.clone(), pkg_config.render(package)))
+            .collect()
+    }
+
+    /// The value of the `key` field of a rendered `.pc` file, if any.
+    fn field<'a>(rendered: &'a str, key: &str) -> Option<&'a str> {
+        rendered.lines().find_map(|line| line.strip_prefix(key)?.strip_prefix(": "))
+    }
+
+    #[test]
+    fn generate_pkg_config_target_linux() {
+        let packages = render_pkg_config(ToolchainPlatform::X86_64_LINUX_GNU, PkgConfigGranularity::Target);
+        assert_eq!(packages.keys().collect::<Vec<_>>(), ["LLVMCore", "LLVMDemangle", "LLVMSupport"]);
+
+        let core = &packages["LLVMCore"];
+        assert!(core.starts_with("prefix=/llvmup/trees/llvmorg-17.0.6/x86_64-linux-gnu\n"));
+        assert_eq!(field(core, "Version"), Some("17.0.6"));
+        assert_eq!(field(core, "Requires"), Some("LLVMSupport"));
+        assert_eq!(field(core, "Cflags"), Some("-I${prefix}/include"));
+        assert_eq!(field(core, "Libs"), Some("-L${prefix}/lib -lLLVMCore"));
+        assert_eq!(field(core, "Libs.private"), Some("-lpthread"));
+
+        // NOTE: The members of the cycle link all of it rather than requiring each other.
+        for name in ["LLVMSupport", "LLVMDemangle"] {
+            let package = &packages[name];
+            assert_eq!(field(package, "Requires"), None);
+            assert_eq!(
+                field(package, "Libs"),
+                Some("-L${prefix}/lib -Wl,--start-group -lLLVMDemangle -lLLVMSupport -Wl,--end-group")
+            );
+            assert_eq!(field(package, "Libs.private"), None);
+        }
+    }
+
+    #[test]
+    fn generate_pkg_config_target_windows() {
+        let packages = render_pkg_config(ToolchainPlatform::X86_64_WINDOWS_MSVC, PkgConfigGranularity::Target);
+        let core = &packages["LLVMCore"];
+        assert_eq!(field(core, "Requires"), Some("LLVMSupport"));
+        assert_eq!(field(core, "Libs"), Some("-L${prefix}/lib LLVMCore.lib"));
+        assert_eq!(field(core, "Libs.private"), Some("pthread.lib"));
+        assert_eq!(
+            field(&packages["LLVMSupport"], "Libs"),
+            Some("-L${prefix}/lib LLVMDemangle.lib LLVMSupport.lib")
+        );
+    }
+
+    #[test]
+    fn generate_pkg_config_component() {
+        let packages = render_pkg_config(ToolchainPlatform::X86_64_LINUX_GNU, PkgConfigGranularity::Component);
+        assert_eq!(packages.keys().collect::<Vec<_>>(), ["llvm"]);
+
+        let llvm = &packages["llvm"];
+        assert_eq!(field(llvm, "Name"), Some("llvm"));
+        assert_eq!(field(llvm, "Description"), Some("The llvm libraries"));
+        assert_eq!(field(llvm, "Requires"), None);
+        assert_eq!(field(llvm, "Cflags"), Some("-I${prefix}/include"));
+        assert_eq!(
+            field(llvm, "Libs"),
+            Some("-L${prefix}/lib -Wl,--start-group -lLLVMCore -lLLVMDemangle -lLLVMSupport -Wl,--end-group")
+        );
+        assert_eq!(field(llvm, "Libs.private"), Some("-lpthread"));
+    }
+
+    #[test]
+    fn system_library_flags() {
+        assert_eq!(system_library_flag(ToolchainSys::Linux, "m"), "-lm");
+        assert_eq!(system_library_flag(ToolchainSys::Macos, "-framework CoreFoundation"), "-framework CoreFoundation");
+        assert_eq!(system_library_flag(ToolchainSys::Windows, "ntdll"), "ntdll.lib");
+        assert_eq!(system_library_flag(ToolchainSys::Windows, "C:/sdk/ws2_32.lib"), "C:/sdk/ws2_32.lib");
+    }
+}
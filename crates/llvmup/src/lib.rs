@@ -17,11 +17,12 @@ use std::{
     hash::{Hash, Hasher},
 };
 
-use crate::toolchain::ToolchainHandle;
-
 #[cfg(feature = "manifest")]
 use std::collections::BTreeMap;
 
+#[cfg(feature = "analysis")]
+use std::sync::Arc;
+
 pub use crate::{
     directories::Directories,
     toolchain::{
@@ -35,15 +36,44 @@ pub use crate::{
         revision::ToolchainRevision,
         variant::ToolchainVariant,
         Toolchain,
+        ToolchainHandle,
         ToolchainInstallOptions,
     },
 };
 
 #[cfg(feature = "analysis")]
-pub use crate::analysis::ToolchainAnalysis;
+pub use crate::analysis::{
+    backends::{ToolchainTargetBackendLibrary, ToolchainTargetBackends},
+    closure::ToolchainLinkClosure,
+    export::ToolchainGraphFormat,
+    genex::GenexContext,
+    llvm_config::ToolchainLlvmConfig,
+    OwnedToolchainAnalysis,
+    ToolchainAnalysis,
+};
+
+#[cfg(feature = "config")]
+pub use crate::{
+    config::{
+        lockfile::{ToolchainLock, ToolchainLockComponent, ToolchainLockfile, TOOLCHAIN_LOCKFILE_NAME},
+        releases::{fetch_available_releases, ToolchainAvailableRelease},
+        ToolchainConfig,
+        ToolchainConfigComponent,
+        ToolchainConfigOverride,
+        ToolchainConfigToolchain,
+        TOOLCHAIN_CONFIG_FILE_NAME,
+    },
+    toolchain::release::ToolchainReleaseConstraint,
+};
 
 #[cfg(feature = "generation")]
-pub use crate::generation::ToolchainConfigGenerator;
+pub use crate::generation::{
+    cargo::{CargoBuildLinkItem, CargoConfig},
+    cmake::CMakeToolchainFile,
+    drift::{GeneratedFileDrift, GeneratedFileDriftHunk, GeneratedFileDriftLine},
+    pkg_config::PkgConfigGranularity,
+    ToolchainConfigGenerator,
+};
 
 #[cfg(feature = "logging")]
 pub use crate::logging::LlvmupLogger;
@@ -51,8 +81,10 @@ pub use crate::logging::LlvmupLogger;
 #[cfg(feature = "manifest")]
 pub use crate::toolchain::component::manifest::{
     ManifestCMakeImportedTarget,
+    ManifestCMakeLocations,
     ManifestCMakeProperties,
     ManifestDistribution,
+    OwnedToolchainComponentManifests,
     ToolchainComponentManifest,
 };
 
@@ -61,7 +93,13 @@ pub use crate::verification::{Checksums, Sha512Digest};
 
 #[cfg(feature = "analysis")]
 mod analysis;
+#[cfg(feature = "build")]
+pub mod build;
+#[cfg(feature = "config")]
+mod config;
 mod directories;
+#[cfg(all(test, feature = "analysis"))]
+mod fixtures;
 #[cfg(feature = "generation")]
 mod generation;
 #[cfg(feature = "logging")]
@@ -95,6 +133,30 @@ pub enum LlvmupToolchainLoadComponentFailureReason {
 
 #[derive(Debug, Snafu)]
 pub enum Error {
+    #[cfg(feature = "analysis")]
+    LlvmupAnalysisClosure {
+        source: crate::analysis::closure::Error,
+    },
+    #[cfg(feature = "analysis")]
+    LlvmupAnalysisExport {
+        source: crate::analysis::export::Error,
+    },
+    #[cfg(feature = "analysis")]
+    LlvmupAnalysisLlvmConfig {
+        source: crate::analysis::llvm_config::Error,
+    },
+    #[cfg(feature = "build")]
+    LlvmupBuild {
+        source: crate::build::Error,
+    },
+    #[cfg(feature = "config")]
+    LlvmupConfig {
+        source: crate::config::Error,
+    },
+    #[cfg(feature = "config")]
+    LlvmupConfigLockfile {
+        source: crate::config::lockfile::Error,
+    },
     LlvmupComponentAssetBundleDownload {
         source: crate::toolchain::component::asset::Error,
     },
@@ -107,6 +169,11 @@ pub enum Error {
     ToolchainAnalysisNotPerformedForComponent {
         component: ToolchainComponent,
     },
+    #[cfg(feature = "analysis")]
+    ToolchainAnalysisPlatformMismatch {
+        expected: ToolchainPlatform,
+        actual: ToolchainPlatform,
+    },
     #[cfg(feature = "generation")]
     LlvmupGeneration {
         source: crate::generation::Error,
@@ -128,6 +195,18 @@ pub enum Error {
         handle: ToolchainHandle,
         component: ToolchainComponent,
     },
+    LlvmupToolchainComponentManifestUnavailable {
+        handle: ToolchainHandle,
+        component: ToolchainComponent,
+    },
+    #[cfg(feature = "manifest")]
+    LlvmupToolchainManifestsParse {
+        source: serde_json::Error,
+    },
+    #[cfg(feature = "analysis")]
+    LlvmupOwnedToolchainAnalysisNew {
+        source: crate::analysis::Error,
+    },
     #[cfg(all(feature = "asm", feature = "serde"))]
     SimdJsonSerdeFromStr {
         source: simd_json::Error,
@@ -136,12 +215,32 @@ pub enum Error {
     SerdeJsonFromStr {
         source: serde_json::Error,
     },
+    #[cfg(feature = "cache")]
+    LlvmupAnalysisCacheLoad {
+        source: crate::analysis::cache::Error,
+    },
+    #[cfg(feature = "cache")]
+    LlvmupAnalysisCacheSerialize {
+        source: serde_json::Error,
+    },
+    #[cfg(feature = "cache")]
+    TokioFsCreateDirAll {
+        source: tokio::io::Error,
+    },
     TokioFsTryExists {
         source: std::io::Error,
     },
     TokioFsReadToString {
         source: tokio::io::Error,
     },
+    #[cfg(feature = "cache")]
+    TokioFsRename {
+        source: tokio::io::Error,
+    },
+    #[cfg(feature = "cache")]
+    TokioFsWrite {
+        source: tokio::io::Error,
+    },
 }
 
 #[cfg_attr(feature = "debug", derive(Debug))]
@@ -160,9 +259,9 @@ impl Llvmup {
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument)]
-    async fn asset_paths_install<'a>(
+    async fn asset_paths_install(
         &self,
-        asset_paths: Vec<ToolchainComponentAsset<'a, Utf8PathBuf>>,
+        asset_paths: Vec<ToolchainComponentAsset<'_, Utf8PathBuf>>,
         options: &ToolchainInstallOptions,
     ) -> Result<(), self::Error> {
         // NOTE: explicitly skip extraction
@@ -228,6 +327,112 @@ impl Llvmup {
         Ok(())
     }
 
+    /// Produce a [`Toolchain`] for each of `platforms` from a project-level `llvmup.toml`.
+    #[cfg(feature = "config")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn config_toolchains(
+        &self,
+        config: &ToolchainConfig,
+        platforms: impl IntoIterator<Item = &ToolchainPlatform> + crate::LlvmupTracingDebug,
+    ) -> Result<Vec<Toolchain>, self::Error> {
+        let mut toolchains = vec![];
+        for platform in platforms {
+            let toolchain = config.toolchain(&self.directories, *platform).await?;
+            toolchains.push(toolchain);
+        }
+        Ok(toolchains)
+    }
+
+    /// Like [`Llvmup::config_toolchains`], but resolving ranges of releases against the releases published for the
+    /// configured variant (rather than the installed ones), so that a release which isn't installed can be selected.
+    #[cfg(feature = "config")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn config_toolchains_from_releases(
+        &self,
+        config: &ToolchainConfig,
+        platforms: impl IntoIterator<Item = &ToolchainPlatform> + crate::LlvmupTracingDebug,
+    ) -> Result<Vec<Toolchain>, self::Error> {
+        // NOTE: Exact releases don't need the published releases, so they can be resolved offline.
+        let ranged = core::iter::once(&config.toolchain.release)
+            .chain(config.toolchain.targets.values().filter_map(|overrides| overrides.release.as_ref()))
+            .any(|release| matches!(release, ToolchainReleaseConstraint::Range(_)));
+        let releases = if ranged {
+            fetch_available_releases(config.toolchain.variant).await?
+        } else {
+            vec![]
+        };
+        let mut toolchains = vec![];
+        for platform in platforms {
+            let toolchain = config.toolchain_from_releases(*platform, &releases)?;
+            toolchains.push(toolchain);
+        }
+        Ok(toolchains)
+    }
+
+    /// Resolve the registered toolchain `handles` into an `llvmup.lock`, fetching each toolchain's checksums file.
+    #[cfg(feature = "config")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn lock_toolchains(
+        &self,
+        handles: impl IntoIterator<Item = &ToolchainHandle> + crate::LlvmupTracingDebug,
+    ) -> Result<ToolchainLockfile, self::Error> {
+        let mut locks = vec![];
+        for handle in handles {
+            let toolchain = self
+                .toolchains
+                .get(&handle.hash)
+                .context(LlvmupToolchainNotRegisteredSnafu { handle: *handle })?;
+            let asset_bundle = {
+                #[cfg(feature = "logging")]
+                let bundle = toolchain.asset_bundle(&self.logger);
+                #[cfg(not(feature = "logging"))]
+                let bundle = toolchain.asset_bundle();
+                bundle
+            }
+            .with_context(|_| LlvmupToolchainsAssetUrlsSnafu)?;
+            let lock = ToolchainLock::resolve(&asset_bundle, &self.directories).await?;
+            locks.push(lock);
+        }
+        Ok(ToolchainLockfile::new(locks))
+    }
+
+    /// Install a registered toolchain, verifying its assets against the digests recorded in `lock` instead of a
+    /// freshly downloaded checksums file.
+    #[cfg(feature = "config")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn install_toolchain_locked(
+        &mut self,
+        handle: ToolchainHandle,
+        options: ToolchainInstallOptions,
+        lock: &ToolchainLock,
+    ) -> Result<(), self::Error> {
+        let toolchain = self
+            .toolchains
+            .get(&handle.hash)
+            .context(LlvmupToolchainNotRegisteredSnafu { handle })?;
+
+        let asset_bundle = {
+            #[cfg(feature = "logging")]
+            let bundle = toolchain.asset_bundle(&self.logger);
+            #[cfg(not(feature = "logging"))]
+            let bundle = toolchain.asset_bundle();
+            bundle
+        }
+        .with_context(|_| LlvmupToolchainsAssetUrlsSnafu)?;
+
+        lock.ensure_bundle(&asset_bundle)?;
+        let checksums = lock.checksums()?;
+
+        let downloaded_asset_paths = asset_bundle
+            .download_with_checksums(&checksums, &self.directories, &options)
+            .await
+            .context(LlvmupComponentAssetBundleDownloadSnafu)?;
+
+        self.asset_paths_install(downloaded_asset_paths, &options).await?;
+
+        Ok(())
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument)]
     pub fn toolchain_components(&self, handle: ToolchainHandle) -> Result<&BTreeSet<ToolchainComponent>, self::Error> {
         let toolchain = self
@@ -284,13 +489,82 @@ impl Llvmup {
             toolchain.components.contains(&component),
             LlvmupToolchainComponentNotRegisteredSnafu { handle, component }
         );
-        let manifest_path = self.directories.manifest_path(toolchain.context, component);
+        // NOTE: Prefer the manifest of an installed tree, otherwise fall back to the standalone manifest cache.
+        let mut manifest_path = self.directories.manifest_path(toolchain.context, component);
+        if !tokio::fs::try_exists(&manifest_path)
+            .await
+            .context(TokioFsTryExistsSnafu)?
+        {
+            manifest_path = self
+                .directories
+                .manifest_cache_path(toolchain.context, component)
+                .context(LlvmupToolchainComponentManifestUnavailableSnafu { handle, component })?;
+            if !tokio::fs::try_exists(&manifest_path)
+                .await
+                .context(TokioFsTryExistsSnafu)?
+            {
+                // NOTE: Boxed, since the download future is otherwise deep enough to overflow the layout queries of
+                // the futures awaiting analyses.
+                Box::pin(self.fetch_toolchain_manifests(handle, [component])).await?;
+            }
+        }
         let manifest = tokio::fs::read_to_string(manifest_path)
             .await
             .context(TokioFsReadToStringSnafu)?;
         Ok(manifest)
     }
 
+    /// Load and parse the manifests of a registered toolchain's `components` into a single self-contained value.
+    #[cfg(all(feature = "analysis", feature = "manifest"))]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn load_toolchain_manifests(
+        &mut self,
+        handle: ToolchainHandle,
+        components: impl IntoIterator<Item = &ToolchainComponent> + crate::LlvmupTracingDebug,
+    ) -> Result<OwnedToolchainComponentManifests, self::Error> {
+        let sources = self.load_toolchain_components(handle, components).await?;
+        OwnedToolchainComponentManifests::parse(sources).context(LlvmupToolchainManifestsParseSnafu)
+    }
+
+    /// Download the standalone manifests of a registered toolchain's `components` (checksum-verified) into the
+    /// manifest cache, so that analysis and generation can proceed without any tree being installed.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn fetch_toolchain_manifests(
+        &self,
+        handle: ToolchainHandle,
+        components: impl IntoIterator<Item = ToolchainComponent> + crate::LlvmupTracingDebug,
+    ) -> Result<(), self::Error> {
+        let toolchain = self
+            .toolchains
+            .get(&handle.hash)
+            .context(LlvmupToolchainNotRegisteredSnafu { handle })?;
+
+        let mut asset_bundle = {
+            #[cfg(feature = "logging")]
+            let bundle = toolchain.asset_bundle(&self.logger);
+            #[cfg(not(feature = "logging"))]
+            let bundle = toolchain.asset_bundle();
+            bundle
+        }
+        .with_context(|_| LlvmupToolchainsAssetUrlsSnafu)?;
+
+        let components = components.into_iter().collect::<BTreeSet<_>>();
+        for component in components.iter().copied() {
+            ensure!(
+                toolchain.components.contains(&component),
+                LlvmupToolchainComponentNotRegisteredSnafu { handle, component }
+            );
+        }
+        asset_bundle.assets.retain(|asset| components.contains(&asset.component));
+
+        asset_bundle
+            .download_manifests(&self.directories, &ToolchainInstallOptions::default())
+            .await
+            .context(LlvmupComponentAssetBundleDownloadSnafu)?;
+
+        Ok(())
+    }
+
     #[cfg(feature = "manifest")]
     pub fn load_toolchain_component_manifests<'a>(
         &'a self,
@@ -325,15 +599,205 @@ impl Llvmup {
         handle: ToolchainHandle,
         components: impl IntoIterator<Item = &'a ToolchainComponent> + crate::LlvmupTracingDebug,
         manifests: &'a BTreeMap<ToolchainComponent, ToolchainComponentManifest<'a>>,
+    ) -> Result<ToolchainAnalysis<'a>, self::Error> {
+        let toolchain = self
+            .toolchains
+            .get(&handle.hash)
+            .context(LlvmupToolchainNotRegisteredSnafu { handle })?;
+        let genex_context = GenexContext::new(toolchain.context.platform);
+        self.analysis_with_genex_context(handle, genex_context, components, manifests)
+    }
+
+    /// Like [`Llvmup::analysis`], but evaluating generator expressions against `genex_context` (e.g., for a `Debug`
+    /// configuration or a different compiler).
+    #[cfg(feature = "analysis")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn analysis_with_genex_context<'a>(
+        &'a self,
+        handle: ToolchainHandle,
+        genex_context: GenexContext<'a>,
+        components: impl IntoIterator<Item = &'a ToolchainComponent> + crate::LlvmupTracingDebug,
+        manifests: &'a BTreeMap<ToolchainComponent, ToolchainComponentManifest<'a>>,
     ) -> Result<ToolchainAnalysis<'a>, self::Error> {
         let components = components.into_iter().copied().collect::<BTreeSet<_>>();
-        let analysis =
-            ToolchainAnalysis::new(handle, components, manifests).context(LlvmupToolchainAnalysisNewSnafu)?;
+        let analysis = ToolchainAnalysis::new(handle, genex_context, components, manifests)
+            .context(LlvmupToolchainAnalysisNewSnafu)?;
         Ok(analysis)
     }
 
+    /// Like [`Llvmup::analysis`], but for all of the components of self-contained `manifests`, producing an analysis
+    /// which doesn't borrow from the caller.
+    #[cfg(feature = "analysis")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn owned_analysis(
+        &self,
+        handle: ToolchainHandle,
+        manifests: Arc<OwnedToolchainComponentManifests>,
+    ) -> Result<OwnedToolchainAnalysis, self::Error> {
+        let toolchain = self
+            .toolchains
+            .get(&handle.hash)
+            .context(LlvmupToolchainNotRegisteredSnafu { handle })?;
+        let genex_context = GenexContext::new(toolchain.context.platform);
+        self.owned_analysis_with_genex_context(handle, genex_context, manifests)
+    }
+
+    /// Like [`Llvmup::owned_analysis`], but evaluating generator expressions against `genex_context`.
+    #[cfg(feature = "analysis")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn owned_analysis_with_genex_context(
+        &self,
+        handle: ToolchainHandle,
+        genex_context: GenexContext<'static>,
+        manifests: Arc<OwnedToolchainComponentManifests>,
+    ) -> Result<OwnedToolchainAnalysis, self::Error> {
+        let analysis = OwnedToolchainAnalysis::new(handle, genex_context, manifests)
+            .context(LlvmupOwnedToolchainAnalysisNewSnafu)?;
+        Ok(analysis)
+    }
+
+    /// Analyse every component of a registered toolchain for each of `platforms` (fetching the standalone manifests of
+    /// those which aren't installed), e.g., to generate a [`CargoConfig`] for all of them with
+    /// [`Llvmup::merged_cargo_config`].
+    #[cfg(all(feature = "analysis", feature = "manifest"))]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn platform_analyses(
+        &mut self,
+        handle: ToolchainHandle,
+        platforms: impl IntoIterator<Item = ToolchainPlatform> + crate::LlvmupTracingDebug,
+    ) -> Result<Vec<(ToolchainContext, OwnedToolchainAnalysis)>, self::Error> {
+        let toolchain = self
+            .toolchains
+            .get(&handle.hash)
+            .context(LlvmupToolchainNotRegisteredSnafu { handle })?;
+        let (context, components) = (toolchain.context, toolchain.components.clone());
+        let mut analyses = vec![];
+        for platform in platforms {
+            let context = ToolchainContext { platform, ..context };
+            let handle = self.register_toolchain(Toolchain {
+                context,
+                components: components.clone(),
+            });
+            let manifests = self.load_toolchain_manifests(handle, &components).await?;
+            analyses.push((context, self.owned_analysis(handle, Arc::new(manifests))?));
+        }
+        Ok(analyses)
+    }
+
+    /// Like [`Llvmup::owned_analysis`] for the manifests of `components`, but reusing a previous analysis of the same
+    /// manifests (by content) from the on-disk cache, and caching the analysis otherwise.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn cached_analysis(
+        &mut self,
+        handle: ToolchainHandle,
+        components: impl IntoIterator<Item = &ToolchainComponent> + crate::LlvmupTracingDebug,
+    ) -> Result<OwnedToolchainAnalysis, self::Error> {
+        let toolchain = self
+            .toolchains
+            .get(&handle.hash)
+            .context(LlvmupToolchainNotRegisteredSnafu { handle })?;
+        let genex_context = GenexContext::new(toolchain.context.platform);
+        self.cached_analysis_with_genex_context(handle, genex_context, components)
+            .await
+    }
+
+    /// Like [`Llvmup::cached_analysis`], but evaluating generator expressions against `genex_context`.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn cached_analysis_with_genex_context(
+        &mut self,
+        handle: ToolchainHandle,
+        genex_context: GenexContext<'static>,
+        components: impl IntoIterator<Item = &ToolchainComponent> + crate::LlvmupTracingDebug,
+    ) -> Result<OwnedToolchainAnalysis, self::Error> {
+        let sources = self.load_toolchain_components(handle, components).await?;
+        let key = crate::analysis::cache::analysis_cache_key(&genex_context, &sources);
+        let cache_dir = self.analysis_cache_dir(handle).await?;
+        let cache_path = cache_dir.join(format!("analysis-{key}.json"));
+
+        match tokio::fs::read_to_string(&cache_path).await {
+            Ok(json) => match OwnedToolchainAnalysis::from_snapshot_json(handle, json) {
+                Ok(analysis) => return Ok(analysis),
+                // NOTE: A cache which is truncated or otherwise corrupt is simply regenerated.
+                Err(err) if err.is_corrupt() => {},
+                Err(source) => return Err(self::Error::LlvmupAnalysisCacheLoad { source }),
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {},
+            Err(source) => return Err(self::Error::TokioFsReadToString { source }),
+        }
+
+        let manifests = OwnedToolchainComponentManifests::parse(sources).context(LlvmupToolchainManifestsParseSnafu)?;
+        let analysis = self.owned_analysis_with_genex_context(handle, genex_context, Arc::new(manifests))?;
+        let json = serde_json::to_string(&analysis.get().snapshot()).context(LlvmupAnalysisCacheSerializeSnafu)?;
+        tokio::fs::create_dir_all(&cache_dir)
+            .await
+            .context(TokioFsCreateDirAllSnafu)?;
+        // NOTE: The cache is renamed into place once fully written, so that a concurrent (or interrupted) build never
+        // reads a partial cache.
+        let temp_path = cache_dir.join(format!("analysis-{key}.json.{}.tmp", std::process::id()));
+        tokio::fs::write(&temp_path, json)
+            .await
+            .context(TokioFsWriteSnafu)?;
+        tokio::fs::rename(&temp_path, &cache_path)
+            .await
+            .context(TokioFsRenameSnafu)?;
+        Ok(analysis)
+    }
+
+    /// Where analyses of a toolchain are cached: `OUT_DIR` when running in a build script, otherwise the toolchain's
+    /// tree (if installed) or its standalone manifest cache.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    async fn analysis_cache_dir(&self, handle: ToolchainHandle) -> Result<Utf8PathBuf, self::Error> {
+        if let Ok(out_dir) = std::env::var("OUT_DIR") {
+            return Ok(Utf8PathBuf::from(out_dir).join("llvmup"));
+        }
+        let toolchain = self
+            .toolchains
+            .get(&handle.hash)
+            .context(LlvmupToolchainNotRegisteredSnafu { handle })?;
+        let tree = self.directories.toolchain_root_path(toolchain.context);
+        if tokio::fs::try_exists(&tree).await.context(TokioFsTryExistsSnafu)? {
+            return Ok(tree.join("share").join("llvmup"));
+        }
+        Ok(self.directories.manifest_cache_dir(toolchain.context))
+    }
+
+    /// Answer `llvm-config` queries for the toolchain an analysis was performed for.
+    #[cfg(feature = "analysis")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn llvm_config<'a>(&self, analysis: &'a ToolchainAnalysis<'a>) -> Result<ToolchainLlvmConfig<'a>, self::Error> {
+        let toolchain = self
+            .toolchains
+            .get(&analysis.handle.hash)
+            .context(LlvmupToolchainNotRegisteredSnafu { handle: analysis.handle })?;
+        let context = ToolchainContext {
+            platform: analysis.platform,
+            ..toolchain.context
+        };
+        let root = self.directories.toolchain_root_path(context);
+        Ok(ToolchainLlvmConfig::new(analysis, context, root))
+    }
+
+    /// A `CMake` toolchain file for a registered toolchain, which uses whichever of its tools it includes.
     #[cfg(feature = "generation")]
     #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn cmake_toolchain_file(&self, handle: ToolchainHandle) -> Result<CMakeToolchainFile<'_>, self::Error> {
+        let toolchain = self
+            .toolchains
+            .get(&handle.hash)
+            .context(LlvmupToolchainNotRegisteredSnafu { handle })?;
+        Ok(CMakeToolchainFile {
+            context: toolchain.context,
+            directories: &self.directories,
+            components: toolchain.components.clone(),
+        })
+    }
+
+    #[cfg(feature = "generation")]
+    #[allow(clippy::needless_pass_by_value)]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
     pub fn generator<'a>(
         &'a self,
         context: ToolchainContext,
@@ -341,6 +805,13 @@ impl Llvmup {
         toolchain_components_crate: BTreeSet<ToolchainComponent>,
         toolchain_components_crate_dependencies: BTreeMap<ToolchainComponent, &'a [&'a str]>,
     ) -> Result<ToolchainConfigGenerator<'a>, self::Error> {
+        ensure!(
+            context.platform == analysis.platform,
+            ToolchainAnalysisPlatformMismatchSnafu {
+                expected: analysis.platform,
+                actual: context.platform,
+            }
+        );
         for component in toolchain_components_crate.iter().copied() {
             ensure!(
                 analysis.components.contains(&component),
@@ -352,11 +823,36 @@ impl Llvmup {
             context,
             &self.directories,
             &analysis.external_targets,
+            &analysis.dependencies,
+            analysis.genex_context,
             toolchain_dependencies_postorder_sccs,
             toolchain_components_crate_dependencies,
         );
         Ok(generation)
     }
+
+    /// Generate a [`CargoConfig`] from each of `analyses` (e.g., those of [`Llvmup::platform_analyses`]) and merge
+    /// them into one whose `build_llvmup.rs` is correct on all of their platforms.
+    #[cfg(feature = "generation")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn merged_cargo_config<'a>(
+        &'a self,
+        analyses: &'a [(ToolchainContext, OwnedToolchainAnalysis)],
+        toolchain_components_crate: &BTreeSet<ToolchainComponent>,
+        toolchain_components_crate_dependencies: &BTreeMap<ToolchainComponent, &'a [&'a str]>,
+    ) -> Result<CargoConfig<'a>, self::Error> {
+        let mut configs = vec![];
+        for (context, analysis) in analyses {
+            let generator = self.generator(
+                *context,
+                analysis.get(),
+                toolchain_components_crate.clone(),
+                toolchain_components_crate_dependencies.clone(),
+            )?;
+            configs.push(generator.generate_cargo_config()?);
+        }
+        Ok(CargoConfig::merge(configs)?)
+    }
 }
 
 #[derive(Default)]
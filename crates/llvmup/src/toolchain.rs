@@ -3,7 +3,6 @@ use std::collections::BTreeSet;
 use url::Url;
 
 use crate::{
-    toolchain::platform::{ToolchainArch, ToolchainSys},
     ToolchainComponent,
     ToolchainComponentAsset,
     ToolchainComponentAssetBundle,
@@ -22,6 +21,46 @@ pub mod release;
 pub mod revision;
 pub mod variant;
 
+// NOTE: The model types are (de)serialized using the same string forms as their `Display` and `FromStr` impls.
+#[cfg(feature = "serde")]
+macro_rules! impl_serde_display_from_str {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl serde::Serialize for $ty {
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for $ty {
+                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+                    value.parse().map_err(serde::de::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "serde")]
+impl_serde_display_from_str!(
+    ToolchainComponent,
+    ToolchainContext,
+    ToolchainPlatform,
+    release::ToolchainRelease,
+    revision::ToolchainRevision,
+    ToolchainVariant,
+);
+
+#[cfg(feature = "config")]
+impl_serde_display_from_str!(release::ToolchainReleaseConstraint);
+
 #[derive(Debug, Snafu)]
 pub enum Error {
     ToolchainComponentRequiresDependency {
@@ -36,6 +75,25 @@ pub enum Error {
         component: ToolchainComponent,
         platform: ToolchainPlatform,
     },
+    ToolchainComponentMoldUnqualified,
+    ToolchainComponentParse {
+        input: String,
+    },
+    ToolchainContextParse {
+        input: String,
+    },
+    ToolchainPlatformParse {
+        input: String,
+    },
+    ToolchainReleaseParse {
+        input: String,
+    },
+    ToolchainRevisionParse {
+        input: String,
+    },
+    ToolchainVariantParse {
+        input: String,
+    },
     UrlParse {
         source: url::ParseError,
     },
@@ -54,7 +112,7 @@ pub struct Toolchain {
 }
 
 impl Toolchain {
-    #[allow(clippy::needless_lifetimes)]
+    #[allow(clippy::elidable_lifetime_names, clippy::needless_lifetimes)]
     #[cfg_attr(feature = "tracing", tracing::instrument)]
     pub fn asset_bundle<'a>(
         &'a self,
@@ -67,7 +125,6 @@ impl Toolchain {
             variant,
             release,
             revision,
-            platform,
             ..
         } = context;
 
@@ -81,7 +138,7 @@ impl Toolchain {
 
         let mut assets = vec![];
         let checksums = {
-            let filename = format!("{variant}-{release}-{platform}{revision}.sha512");
+            let filename = format!("{context}.sha512");
             Url::parse(&format!("{base_url}/{filename}")).context(UrlParseSnafu)?
         };
         for component in &self.components {
@@ -155,8 +212,7 @@ impl Toolchain {
                 ToolchainComponent::ToolLld => {},
                 ToolchainComponent::ToolMold { .. } => {
                     snafu::ensure!(
-                        toolchain.context.platform.sys() == ToolchainSys::Linux
-                            && toolchain.context.platform.arch() != ToolchainArch::I686,
+                        ToolchainComponent::mold_supports_platform(toolchain.context.platform),
                         ToolchainComponentUnsupportedPlatformSnafu {
                             component: *component,
                             platform: toolchain.context.platform,
@@ -187,6 +243,8 @@ pub struct ToolchainHandle {
 }
 
 #[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
 #[derive(Default)]
 pub struct ToolchainInstallOptions {
     pub download: Option<bool>,
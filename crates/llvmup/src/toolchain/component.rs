@@ -2,7 +2,12 @@ use camino::Utf8Path;
 use snafu::prelude::*;
 use url::Url;
 
-use crate::{ToolchainContext, ToolchainPlatform, ToolchainRelease};
+use crate::{
+    toolchain::platform::{ToolchainArch, ToolchainSys},
+    ToolchainContext,
+    ToolchainPlatform,
+    ToolchainRelease,
+};
 
 pub mod asset;
 pub mod checksum;
@@ -120,7 +125,40 @@ impl core::fmt::Display for ToolchainComponent {
             Self::Swift => write!(f, "swift"),
             Self::ToolClang => write!(f, "tool_clang"),
             Self::ToolLld => write!(f, "tool_lld"),
-            Self::ToolMold { .. } => write!(f, "tool_mold"),
+            // NOTE: The release and platform are spelled out so that the component can be parsed back.
+            Self::ToolMold { platform, release } => write!(f, "tool_mold-{release}-{platform}"),
+        }
+    }
+}
+
+impl core::str::FromStr for ToolchainComponent {
+    type Err = crate::toolchain::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "clang" => Ok(Self::Clang),
+            "llvm" => Ok(Self::Llvm),
+            "mlir" => Ok(Self::Mlir),
+            "swift" => Ok(Self::Swift),
+            "tool_clang" => Ok(Self::ToolClang),
+            "tool_lld" => Ok(Self::ToolLld),
+            // NOTE: Unlike in `llvmup.toml`, there is no configuration here to take the release and platform from.
+            "tool_mold" => Err(crate::toolchain::Error::ToolchainComponentMoldUnqualified),
+            _ => {
+                let (release, platform) = input
+                    .strip_prefix("tool_mold-")
+                    .and_then(|mold| mold.split_once('-'))
+                    .and_then(|(release, platform)| Some((release.parse().ok()?, platform.parse().ok()?)))
+                    .with_context(|| crate::toolchain::ToolchainComponentParseSnafu {
+                        input: input.to_owned(),
+                    })?;
+                let component = Self::ToolMold { platform, release };
+                ensure!(
+                    Self::mold_supports_platform(platform),
+                    crate::toolchain::ToolchainComponentUnsupportedPlatformSnafu { component, platform }
+                );
+                Ok(component)
+            },
         }
     }
 }
@@ -139,6 +177,13 @@ impl ToolchainComponent {
         }
     }
 
+    /// Whether `mold` publishes builds for `platform` (only Linux, other than 32-bit x86).
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn mold_supports_platform(platform: ToolchainPlatform) -> bool {
+        platform.sys() == ToolchainSys::Linux && platform.arch() != ToolchainArch::I686
+    }
+
     #[must_use]
     #[cfg_attr(feature = "tracing", tracing::instrument)]
     pub fn tree_name_mold(platform: &ToolchainPlatform, release: &ToolchainRelease) -> String {
@@ -164,13 +209,13 @@ impl ToolchainComponent {
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument)]
-    pub fn asset_url_other(&self, context: &ToolchainContext) -> Result<Url, url::ParseError> {
+    fn release_url_other(context: &ToolchainContext, repo_file: &str) -> Result<Url, url::ParseError> {
         use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
         let ToolchainContext {
             variant,
             release,
             revision,
-            platform,
+            ..
         } = context;
         let release_dir = {
             const FRAGMENT: &AsciiSet = &CONTROLS.add(b'+');
@@ -178,11 +223,46 @@ impl ToolchainComponent {
             utf8_percent_encode(&input, FRAGMENT).to_string()
         };
         let repo_base = "https://github.com/llvmup/toolchains";
-        let repo_file = format!("{self}-{variant}-{release}-{platform}{revision}.tar.xz");
         let url = format!("{repo_base}/releases/download/{release_dir}/{repo_file}");
         Url::parse(&url)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn asset_url_other(&self, context: &ToolchainContext) -> Result<Url, url::ParseError> {
+        let ToolchainContext {
+            variant,
+            release,
+            revision,
+            platform,
+        } = context;
+        let repo_file = format!("{self}-{variant}-{release}-{platform}{revision}.tar.xz");
+        Self::release_url_other(context, &repo_file)
+    }
+
+    /// The file name of the standalone `llvmup.json` manifest published alongside the component's tarball, or `None`
+    /// for components (i.e., `tool_mold`) which don't ship a manifest.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn manifest_file_name(&self, context: &ToolchainContext) -> Option<String> {
+        if let ToolchainComponent::ToolMold { .. } = self {
+            return None;
+        }
+        let ToolchainContext {
+            variant,
+            release,
+            revision,
+            platform,
+        } = context;
+        Some(format!("{self}-{variant}-{release}-{platform}{revision}.llvmup.json"))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn manifest_url(&self, context: &ToolchainContext) -> Result<Option<Url>, url::ParseError> {
+        self.manifest_file_name(context)
+            .map(|repo_file| Self::release_url_other(context, &repo_file))
+            .transpose()
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument)]
     pub fn asset_url(&self, context: &ToolchainContext) -> Result<Url, url::ParseError> {
         match self {
@@ -191,3 +271,51 @@ impl ToolchainComponent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mold() -> ToolchainComponent {
+        ToolchainComponent::ToolMold {
+            platform: ToolchainPlatform::X86_64_LINUX_GNU,
+            release: ToolchainRelease::new(2, 4, Some(0)),
+        }
+    }
+
+    #[test]
+    fn mold_round_trip() {
+        assert_eq!(mold().to_string(), "tool_mold-2.4.0-x86_64-linux-gnu");
+        assert_eq!("tool_mold-2.4.0-x86_64-linux-gnu".parse::<ToolchainComponent>().unwrap(), mold());
+    }
+
+    #[test]
+    fn mold_unqualified() {
+        assert!(matches!(
+            "tool_mold".parse::<ToolchainComponent>(),
+            Err(crate::toolchain::Error::ToolchainComponentMoldUnqualified)
+        ));
+    }
+
+    #[test]
+    fn mold_unsupported_platform() {
+        assert!(matches!(
+            "tool_mold-2.4.0-x86_64-windows-msvc".parse::<ToolchainComponent>(),
+            Err(crate::toolchain::Error::ToolchainComponentUnsupportedPlatform { .. })
+        ));
+        assert!(matches!(
+            "tool_mold-2.4.0-i686-linux-gnu".parse::<ToolchainComponent>(),
+            Err(crate::toolchain::Error::ToolchainComponentUnsupportedPlatform { .. })
+        ));
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn serde_matches_display() {
+        for component in [ToolchainComponent::Llvm, ToolchainComponent::ToolLld, mold()] {
+            let json = serde_json::to_string(&component).unwrap();
+            assert_eq!(json, format!("\"{component}\""));
+            assert_eq!(serde_json::from_str::<ToolchainComponent>(&json).unwrap(), component);
+        }
+    }
+}
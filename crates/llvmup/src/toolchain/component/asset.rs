@@ -1,4 +1,4 @@
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use snafu::prelude::*;
 use url::Url;
 
@@ -13,6 +13,13 @@ pub enum Error {
         source: crate::toolchain::component::download::Error,
     },
     LlvmupComponentAssetUrlMissingFileSegment,
+    LlvmupComponentManifestUrl {
+        source: url::ParseError,
+    },
+    #[cfg(feature = "verification")]
+    LlvmupComponentManifestChecksumMissing {
+        file_name: String,
+    },
     LlvmupComponentChecksum {
         source: crate::toolchain::component::checksum::Error,
     },
@@ -27,6 +34,9 @@ pub enum Error {
     StdIoTryExists {
         source: std::io::Error,
     },
+    TokioFsCreateDirAll {
+        source: tokio::io::Error,
+    },
     TokioFsMetadata {
         source: tokio::io::Error,
     },
@@ -51,6 +61,22 @@ impl<'a> ToolchainComponentAsset<'a, Url> {
         #[cfg(feature = "verification")] checksums: &crate::Checksums<'_>,
         dirs: &crate::Directories,
         options: &ToolchainInstallOptions,
+    ) -> Result<ToolchainComponentAsset<'a, Utf8PathBuf>, self::Error> {
+        self.download_and_checksum_into(
+            #[cfg(feature = "verification")]
+            checksums,
+            dirs.downloads(),
+            options,
+        )
+        .await
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    async fn download_and_checksum_into(
+        &self,
+        #[cfg(feature = "verification")] checksums: &crate::Checksums<'_>,
+        dir: &Utf8Path,
+        options: &ToolchainInstallOptions,
     ) -> Result<ToolchainComponentAsset<'a, Utf8PathBuf>, self::Error> {
         let Some(filename) = self.uri.path_segments().and_then(std::iter::Iterator::last) else {
             return Err(self::Error::LlvmupComponentAssetUrlMissingFileSegment);
@@ -62,7 +88,7 @@ impl<'a> ToolchainComponentAsset<'a, Url> {
             .report_asset_download(self)
             .await
             .context(LlvmupLoggingSnafu)?;
-        let path = dirs.downloads().join(filename);
+        let path = dir.join(filename);
 
         if options.download == Some(false)
             || (options.download.is_none() && path.try_exists().context(StdIoTryExistsSnafu)?)
@@ -82,7 +108,7 @@ impl<'a> ToolchainComponentAsset<'a, Url> {
             let future = crate::toolchain::component::checksum::verify_checksum_of_filename(
                 feedback,
                 checksums,
-                dirs.downloads(),
+                dir,
                 filename.into(),
                 options,
             );
@@ -90,7 +116,7 @@ impl<'a> ToolchainComponentAsset<'a, Url> {
             #[cfg(all(not(feature = "logging"), feature = "verification"))]
             let future = crate::toolchain::component::checksum::verify_checksum_of_filename(
                 checksums,
-                dirs.downloads(),
+                dir,
                 filename.into(),
                 options,
             );
@@ -142,38 +168,132 @@ impl<'a> ToolchainComponentAssetBundle<'a> {
         dirs: &crate::Directories,
         options: &ToolchainInstallOptions,
     ) -> Result<Vec<ToolchainComponentAsset<'a, Utf8PathBuf>>, self::Error> {
-        let ToolchainComponentAssetBundle {
-            #[cfg(feature = "verification")]
-            checksums,
-            assets,
-            ..
-        } = &self;
+        #[cfg(feature = "verification")]
+        let checksums_text = self.download_checksums(dirs).await?;
 
-        #[cfg(all(feature = "logging", feature = "verification"))]
-        let checksums_path = crate::toolchain::component::download::download_checksums(self.logger, dirs, checksums)
-            .await
-            .context(LlvmupComponentDownloadSnafu)?;
+        #[cfg(feature = "verification")]
+        let checksums =
+            crate::verification::parse_sha512_checksums(&checksums_text).context(LlvmupDigestLoadChecksumsSnafu)?;
 
-        #[cfg(all(not(feature = "logging"), feature = "verification"))]
-        let checksums_path = crate::toolchain::component::download::download_checksums(dirs, checksums)
+        #[cfg(feature = "verification")]
+        let future = self.download_with_checksums(&checksums, dirs, options);
+        #[cfg(not(feature = "verification"))]
+        let future = self.download_assets(dirs, options);
+
+        future.await
+    }
+
+    /// Download (if needed) the bundle's `.sha512` checksums file and return its contents.
+    #[cfg(feature = "verification")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn download_checksums(&self, dirs: &crate::Directories) -> Result<String, self::Error> {
+        #[cfg(feature = "logging")]
+        let checksums_path =
+            crate::toolchain::component::download::download_checksums(self.logger, dirs, &self.checksums)
+                .await
+                .context(LlvmupComponentDownloadSnafu)?;
+
+        #[cfg(not(feature = "logging"))]
+        let checksums_path = crate::toolchain::component::download::download_checksums(dirs, &self.checksums)
             .await
             .context(LlvmupComponentDownloadSnafu)?;
 
-        #[cfg(feature = "verification")]
         let checksums_text = tokio::fs::read_to_string(&checksums_path)
             .await
             .context(TokioFsReadToStringSnafu)?;
 
+        Ok(checksums_text)
+    }
+
+    /// Download (if needed) the standalone `llvmup.json` manifests of the bundle's components into the manifest
+    /// cache, verified against the bundle's checksums file, without fetching any of the component tarballs.
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn download_manifests(
+        &self,
+        dirs: &crate::Directories,
+        options: &ToolchainInstallOptions,
+    ) -> Result<Vec<ToolchainComponentAsset<'a, Utf8PathBuf>>, self::Error> {
+        #[cfg(feature = "verification")]
+        let checksums_text = self.download_checksums(dirs).await?;
+
         #[cfg(feature = "verification")]
         let checksums =
             crate::verification::parse_sha512_checksums(&checksums_text).context(LlvmupDigestLoadChecksumsSnafu)?;
 
-        let mut asset_paths = vec![];
+        let dir = dirs.manifest_cache_dir(*self.context);
+        tokio::fs::create_dir_all(&dir).await.context(TokioFsCreateDirAllSnafu)?;
+
+        let mut manifest_paths = vec![];
 
-        for asset in assets {
+        for asset in &self.assets {
+            let Some(uri) = asset
+                .component
+                .manifest_url(self.context)
+                .context(LlvmupComponentManifestUrlSnafu)?
+            else {
+                continue;
+            };
+
+            // NOTE: Unlike tarballs, a manifest missing from the checksums file is an error rather than unverified.
             #[cfg(feature = "verification")]
-            let asset = asset.download_and_checksum(&checksums, dirs, options).await?;
-            #[cfg(not(feature = "verification"))]
+            if let Some(file_name) = asset.component.manifest_file_name(self.context) {
+                ensure!(
+                    checksums.contains_key(Utf8Path::new(&file_name)),
+                    LlvmupComponentManifestChecksumMissingSnafu { file_name }
+                );
+            }
+
+            let manifest = ToolchainComponentAsset {
+                #[cfg(feature = "logging")]
+                logger: self.logger,
+                context: self.context,
+                component: asset.component,
+                uri,
+            };
+            let manifest = manifest
+                .download_and_checksum_into(
+                    #[cfg(feature = "verification")]
+                    &checksums,
+                    &dir,
+                    options,
+                )
+                .await?;
+            manifest_paths.push(manifest);
+        }
+
+        Ok(manifest_paths)
+    }
+
+    /// Download the bundle's assets, verifying them against `checksums` rather than the bundle's checksums file
+    /// (e.g., digests recorded in an `llvmup.lock`).
+    #[cfg(feature = "verification")]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub async fn download_with_checksums(
+        &self,
+        checksums: &crate::Checksums<'_>,
+        dirs: &crate::Directories,
+        options: &ToolchainInstallOptions,
+    ) -> Result<Vec<ToolchainComponentAsset<'a, Utf8PathBuf>>, self::Error> {
+        let mut asset_paths = vec![];
+
+        for asset in &self.assets {
+            let asset = asset.download_and_checksum(checksums, dirs, options).await?;
+            asset_paths.push(asset);
+        }
+
+        Ok(asset_paths)
+    }
+
+    #[cfg(not(feature = "verification"))]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    async fn download_assets(
+        &self,
+        dirs: &crate::Directories,
+        options: &ToolchainInstallOptions,
+    ) -> Result<Vec<ToolchainComponentAsset<'a, Utf8PathBuf>>, self::Error> {
+        let mut asset_paths = vec![];
+
+        for asset in &self.assets {
             let asset = asset.download(dirs, options).await?;
             asset_paths.push(asset);
         }
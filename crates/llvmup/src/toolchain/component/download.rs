@@ -122,7 +122,7 @@ pub async fn checksum_and_download_url_to_path(
     #[cfg(feature = "verification")]
     let expected = path
         .components()
-        .last()
+        .next_back()
         .and_then(|filename| checksums.get(Utf8Path::new(filename.as_str())));
 
     #[cfg(feature = "logging")]
@@ -23,7 +23,7 @@ impl crate::Directories {
         asset_install_inner(dest, path).await?;
         let tree_name = ToolchainComponent::tree_name_mold(platform, release);
         let from = dest.join(&tree_name);
-        let into = dest.join(format!("mold-{release}")).join(platform.to_string());
+        let into = self.mold_root_path(*platform, *release);
         tokio::fs::rename(from, into).await.context(TokioFsRenameSnafu)?;
         Ok(())
     }
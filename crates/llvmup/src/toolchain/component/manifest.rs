@@ -1,9 +1,70 @@
 use indexmap::IndexMap;
-use serde::Deserialize;
-use zerovec::VarZeroVec;
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+use yoke::{Yoke, Yokeable};
+use zerovec::{VarZeroSlice, VarZeroVec};
 
 use crate::ToolchainComponent;
 
+/// Component manifests together with the sources they borrow from, so that they can be stored, returned from
+/// functions, and sent across threads without the caller keeping the sources alive.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct OwnedToolchainComponentManifests {
+    yoke: Yoke<ToolchainComponentManifests<'static>, Arc<BTreeMap<ToolchainComponent, String>>>,
+}
+
+impl OwnedToolchainComponentManifests {
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn parse(sources: BTreeMap<ToolchainComponent, String>) -> Result<Self, serde_json::Error> {
+        // NOTE: The sources are shared by the cart, so the in-place (mutating) `simd-json` parser can't be used here.
+        let yoke = Yoke::try_attach_to_cart(Arc::new(sources), |sources| {
+            sources
+                .iter()
+                .map(|(component, source)| Ok((*component, serde_json::from_str(source)?)))
+                .collect::<Result<_, _>>()
+                .map(ToolchainComponentManifests)
+        })?;
+        Ok(Self { yoke })
+    }
+
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn get(&self) -> &BTreeMap<ToolchainComponent, ToolchainComponentManifest<'_>> {
+        &self.yoke.get().0
+    }
+
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn sources(&self) -> &BTreeMap<ToolchainComponent, String> {
+        self.yoke.backing_cart()
+    }
+
+    /// The configurations any of the inherent targets were imported in (e.g., `RELEASE`).
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn imported_configurations(&self) -> BTreeSet<&str> {
+        self.get()
+            .values()
+            .flat_map(|manifest| manifest.cmake_properties.imported_targets.values())
+            .filter_map(|target| match target {
+                ManifestCMakeImportedTarget::Inherent { inherent_target, .. } => {
+                    Some(inherent_target.imported_configurations())
+                },
+                ManifestCMakeImportedTarget::Adjacent { .. } => None,
+            })
+            .flat_map(VarZeroSlice::iter)
+            .collect()
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Yokeable)]
+struct ToolchainComponentManifests<'a>(BTreeMap<ToolchainComponent, ToolchainComponentManifest<'a>>);
+
 #[cfg_attr(feature = "debug", derive(Debug))]
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,7 +78,7 @@ pub struct ToolchainComponentManifest<'a> {
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub struct ManifestCMakeProperties<'a> {
     #[serde(borrow)]
-    pub imported_targets: IndexMap<&'a str, ManifestCMakeImportedTarget<'a>>,
+    pub imported_targets: IndexMap<Cow<'a, str>, ManifestCMakeImportedTarget<'a>>,
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -35,7 +96,8 @@ pub enum ManifestCMakeImportedTarget<'a> {
     Inherent {
         #[serde(deserialize_with = "deserialize_cmake_bool")]
         imported: bool,
-        name: &'a str,
+        #[serde(borrow)]
+        name: Cow<'a, str>,
         #[serde(deserialize_with = "deserialize_cmake_bool")]
         system: bool,
         #[serde(flatten)]
@@ -44,7 +106,7 @@ pub enum ManifestCMakeImportedTarget<'a> {
 }
 
 #[cfg_attr(feature = "debug", derive(Debug))]
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize, Serialize)]
 #[serde(tag = "TYPE", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ManifestCMakeInherentTarget<'a> {
     #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -53,70 +115,365 @@ pub enum ManifestCMakeInherentTarget<'a> {
         #[serde(default)]
         imported_configurations: VarZeroVec<'a, str>,
         #[serde(default)]
+        interface_compile_definitions: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_features: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_options: VarZeroVec<'a, str>,
+        #[serde(default)]
         interface_include_directories: VarZeroVec<'a, str>,
         #[serde(default)]
         interface_link_directories: VarZeroVec<'a, str>,
         #[serde(default)]
         interface_link_libraries: VarZeroVec<'a, str>,
-        location: &'a str,
-        #[serde(rename = "LOCATION_<CONFIG>")]
-        location_config: &'a str,
-        macosx_package_location: &'a str,
-        vs_deployment_location: &'a str,
+        #[serde(default)]
+        interface_link_options: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_system_include_directories: VarZeroVec<'a, str>,
+        #[serde(borrow)]
+        location: Cow<'a, str>,
+        #[serde(flatten)]
+        locations: ManifestCMakeLocations<'a>,
+        #[serde(borrow)]
+        macosx_package_location: Cow<'a, str>,
+        #[serde(borrow)]
+        vs_deployment_location: Cow<'a, str>,
     },
     #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
     InterfaceLibrary {
+        #[serde(default)]
+        interface_compile_definitions: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_features: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_options: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_include_directories: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_link_directories: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_link_libraries: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_link_options: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_system_include_directories: VarZeroVec<'a, str>,
+    },
+    /// A library loaded at runtime (e.g., an LLVM pass plugin) which is never linked.
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    ModuleLibrary {
+        #[serde(default)]
+        imported_configurations: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_definitions: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_features: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_options: VarZeroVec<'a, str>,
         #[serde(default)]
         interface_include_directories: VarZeroVec<'a, str>,
         #[serde(default)]
         interface_link_directories: VarZeroVec<'a, str>,
         #[serde(default)]
         interface_link_libraries: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_link_options: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_system_include_directories: VarZeroVec<'a, str>,
+        #[serde(borrow)]
+        location: Cow<'a, str>,
+        #[serde(flatten)]
+        locations: ManifestCMakeLocations<'a>,
+        #[serde(borrow)]
+        macosx_package_location: Cow<'a, str>,
+        #[serde(borrow)]
+        vs_deployment_location: Cow<'a, str>,
+    },
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    ObjectLibrary {
+        #[serde(default)]
+        imported_configurations: VarZeroVec<'a, str>,
+        #[serde(default)]
+        imported_objects: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_definitions: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_features: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_options: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_include_directories: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_link_directories: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_link_libraries: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_link_options: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_system_include_directories: VarZeroVec<'a, str>,
     },
     #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
     SharedLibrary {
         #[serde(default)]
-        #[serde(deserialize_with = "deserialize_cmake_bool")]
+        #[serde(deserialize_with = "deserialize_cmake_bool", serialize_with = "serialize_cmake_bool")]
         framework: bool,
         // #[serde(default)]
-        // framework_version: Option<&'a str>,
+        // framework_version: Option<Cow<'a, str>>,
         #[serde(default)]
         imported_configurations: VarZeroVec<'a, str>,
         #[serde(default)]
+        imported_link_interface_languages: VarZeroVec<'a, str>,
+        #[serde(default)]
+        imported_implib: Option<Cow<'a, str>>,
+        #[serde(default)]
+        imported_soname: Option<Cow<'a, str>>,
+        #[serde(default)]
+        interface_compile_definitions: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_features: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_options: VarZeroVec<'a, str>,
+        #[serde(default)]
         interface_include_directories: VarZeroVec<'a, str>,
         #[serde(default)]
         interface_link_directories: VarZeroVec<'a, str>,
         #[serde(default)]
         interface_link_libraries: VarZeroVec<'a, str>,
-        location: &'a str,
-        #[serde(rename = "LOCATION_<CONFIG>")]
-        location_config: &'a str,
-        macosx_package_location: &'a str,
-        vs_deployment_location: &'a str,
+        #[serde(default)]
+        interface_link_options: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_system_include_directories: VarZeroVec<'a, str>,
+        #[serde(borrow)]
+        location: Cow<'a, str>,
+        #[serde(flatten)]
+        locations: ManifestCMakeLocations<'a>,
+        #[serde(borrow)]
+        macosx_package_location: Cow<'a, str>,
+        #[serde(borrow)]
+        vs_deployment_location: Cow<'a, str>,
     },
     #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
     StaticLibrary {
         #[serde(default)]
-        #[serde(deserialize_with = "deserialize_cmake_bool")]
+        #[serde(deserialize_with = "deserialize_cmake_bool", serialize_with = "serialize_cmake_bool")]
         framework: bool,
         // #[serde(default)]
-        // framework_version: Option<&'a str>,
+        // framework_version: Option<Cow<'a, str>>,
         #[serde(default)]
         imported_configurations: VarZeroVec<'a, str>,
         #[serde(default)]
+        imported_link_interface_languages: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_definitions: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_features: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_options: VarZeroVec<'a, str>,
+        #[serde(default)]
         interface_include_directories: VarZeroVec<'a, str>,
         #[serde(default)]
         interface_link_directories: VarZeroVec<'a, str>,
         #[serde(default)]
         interface_link_libraries: VarZeroVec<'a, str>,
-        location: &'a str,
-        #[serde(rename = "LOCATION_<CONFIG>")]
-        location_config: &'a str,
-        macosx_package_location: &'a str,
-        vs_deployment_location: &'a str,
+        #[serde(default)]
+        interface_link_options: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_system_include_directories: VarZeroVec<'a, str>,
+        #[serde(borrow)]
+        location: Cow<'a, str>,
+        #[serde(flatten)]
+        locations: ManifestCMakeLocations<'a>,
+        #[serde(borrow)]
+        macosx_package_location: Cow<'a, str>,
+        #[serde(borrow)]
+        vs_deployment_location: Cow<'a, str>,
+    },
+    /// A library of unspecified kind, whose linkage is inferred from its location.
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    UnknownLibrary {
+        #[serde(default)]
+        imported_configurations: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_definitions: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_features: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_compile_options: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_include_directories: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_link_directories: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_link_libraries: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_link_options: VarZeroVec<'a, str>,
+        #[serde(default)]
+        interface_system_include_directories: VarZeroVec<'a, str>,
+        #[serde(borrow)]
+        location: Cow<'a, str>,
+        #[serde(flatten)]
+        locations: ManifestCMakeLocations<'a>,
+        #[serde(borrow)]
+        macosx_package_location: Cow<'a, str>,
+        #[serde(borrow)]
+        vs_deployment_location: Cow<'a, str>,
     },
 }
 
+impl ManifestCMakeInherentTarget<'_> {
+    /// The configurations the target was imported in (none for interface libraries).
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn imported_configurations(&self) -> &VarZeroSlice<str> {
+        match self {
+            Self::Executable {
+                imported_configurations,
+                ..
+            }
+            | Self::ModuleLibrary {
+                imported_configurations,
+                ..
+            }
+            | Self::ObjectLibrary {
+                imported_configurations,
+                ..
+            }
+            | Self::SharedLibrary {
+                imported_configurations,
+                ..
+            }
+            | Self::StaticLibrary {
+                imported_configurations,
+                ..
+            }
+            | Self::UnknownLibrary {
+                imported_configurations,
+                ..
+            } => imported_configurations,
+            Self::InterfaceLibrary { .. } => VarZeroSlice::new_empty(),
+        }
+    }
+}
+
+/// The per-configuration locations of an imported target (i.e., `IMPORTED_LOCATION_<CONFIG>`) and of its import
+/// library (i.e., `IMPORTED_IMPLIB_<CONFIG>`), keyed by the upper-case configuration name.
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[derive(Clone, Default)]
+pub struct ManifestCMakeLocations<'a> {
+    pub locations: BTreeMap<Cow<'a, str>, Cow<'a, str>>,
+    pub implibs: BTreeMap<Cow<'a, str>, Cow<'a, str>>,
+}
+
+impl<'a> ManifestCMakeLocations<'a> {
+    /// Select the location for `config`, falling back (as `CMake` does) to the first of `imported_configurations`
+    /// that has one, and then to the configuration-independent `location`.
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn select(&'a self, config: &str, imported_configurations: &VarZeroSlice<str>, location: &'a str) -> &'a str {
+        select_config(&self.locations, config, imported_configurations).unwrap_or(location)
+    }
+
+    /// Select the import library for `config`, with the same fallbacks as [`ManifestCMakeLocations::select`].
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn select_implib(
+        &'a self,
+        config: &str,
+        imported_configurations: &VarZeroSlice<str>,
+        implib: Option<&'a str>,
+    ) -> Option<&'a str> {
+        select_config(&self.implibs, config, imported_configurations).or(implib)
+    }
+}
+
+fn select_config<'a>(
+    properties: &'a BTreeMap<Cow<'a, str>, Cow<'a, str>>,
+    config: &str,
+    imported_configurations: &VarZeroSlice<str>,
+) -> Option<&'a str> {
+    let config = config.to_ascii_uppercase();
+    if let Some(property) = properties.get(config.as_str()) {
+        return Some(property);
+    }
+    imported_configurations
+        .iter()
+        .find_map(|config| properties.get(config.to_ascii_uppercase().as_str()))
+        .map(AsRef::as_ref)
+}
+
+impl Serialize for ManifestCMakeLocations<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let locations = self
+            .locations
+            .iter()
+            .map(|(config, location)| (format!("IMPORTED_LOCATION_{config}"), location));
+        let implibs = self
+            .implibs
+            .iter()
+            .map(|(config, implib)| (format!("IMPORTED_IMPLIB_{config}"), implib));
+        serializer.collect_map(locations.chain(implibs))
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for ManifestCMakeLocations<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor<'a>(core::marker::PhantomData<&'a ()>);
+
+        impl<'de: 'a, 'a> serde::de::Visitor<'de> for Visitor<'a> {
+            type Value = ManifestCMakeLocations<'a>;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("a map of target properties")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                // NOTE: Skip the literal (unexpanded) `<CONFIG>` properties.
+                let config = |key: &Cow<'a, str>, prefixes: &[&str]| {
+                    prefixes
+                        .iter()
+                        .find_map(|prefix| strip_cow_prefix(key, prefix))
+                        .filter(|config| !config.is_empty() && !config.contains('<'))
+                };
+                let mut locations = ManifestCMakeLocations::default();
+                while let Some(ManifestStr(key)) = map.next_key()? {
+                    if let Some(config) = config(&key, &["IMPORTED_LOCATION_", "LOCATION_"]) {
+                        let ManifestStr(location) = map.next_value()?;
+                        locations.locations.insert(config, location);
+                    } else if let Some(config) = config(&key, &["IMPORTED_IMPLIB_"]) {
+                        let ManifestStr(implib) = map.next_value()?;
+                        locations.implibs.insert(config, implib);
+                    } else {
+                        map.next_value::<serde::de::IgnoredAny>()?;
+                    }
+                }
+                Ok(locations)
+            }
+        }
+
+        deserializer.deserialize_map(Visitor(core::marker::PhantomData))
+    }
+}
+
+/// A string borrowed from the manifest source unless it had to be unescaped.
+#[derive(Deserialize)]
+struct ManifestStr<'a>(#[serde(borrow)] Cow<'a, str>);
+
+/// Strip `prefix` from `value`, still borrowing from the source if `value` does.
+fn strip_cow_prefix<'a>(value: &Cow<'a, str>, prefix: &str) -> Option<Cow<'a, str>> {
+    match value {
+        Cow::Borrowed(value) => value.strip_prefix(prefix).map(Cow::Borrowed),
+        Cow::Owned(value) => value.strip_prefix(prefix).map(|value| Cow::Owned(String::from(value))),
+    }
+}
+
 #[cfg_attr(feature = "debug", derive(Debug))]
 #[derive(Clone, Copy, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -146,10 +503,104 @@ fn deserialize_cmake_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
-    let value = <&str>::deserialize(deserializer)?;
-    match value {
+    let ManifestStr(value) = ManifestStr::deserialize(deserializer)?;
+    match &*value {
         "TRUE" | "ON" => Ok(true),
         "FALSE" | "OFF" => Ok(false),
         _ => Err(serde::de::Error::custom(format!("expected a valid CMake boolean value, found `{value}`"))),
     }
 }
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn serialize_cmake_bool<S>(value: &bool, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(if *value { "TRUE" } else { "FALSE" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"{
+        "cmakeProperties": {
+            "IMPORTED_TARGETS": {
+                "LLVMSupport": {
+                    "llvmupTargetKind": "inherent",
+                    "IMPORTED": "TRUE",
+                    "NAME": "LLVMSupport",
+                    "SYSTEM": "FALSE",
+                    "TYPE": "STATIC_LIBRARY",
+                    "IMPORTED_CONFIGURATIONS": ["RELEASE"],
+                    "INTERFACE_INCLUDE_DIRECTORIES": ["include\\llvm"],
+                    "LOCATION": "lib\\libLLVMSupport.a",
+                    "IMPORTED_LOCATION_RELEASE": "lib\\release\\libLLVMSupport.a",
+                    "MACOSX_PACKAGE_LOCATION": "",
+                    "VS_DEPLOYMENT_LOCATION": ""
+                },
+                "LLVMDemangle": {
+                    "llvmupTargetKind": "inherent",
+                    "IMPORTED": "TRUE",
+                    "NAME": "LLVMDemangle",
+                    "SYSTEM": "FALSE",
+                    "TYPE": "SHARED_LIBRARY",
+                    "IMPORTED_SONAME": "lib\\LLVMDemangle.so",
+                    "LOCATION": "lib/libLLVMDemangle.so",
+                    "MACOSX_PACKAGE_LOCATION": "",
+                    "VS_DEPLOYMENT_LOCATION": ""
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn parse_escaped_strings() {
+        let sources = BTreeMap::from([(ToolchainComponent::Llvm, String::from(MANIFEST))]);
+        let manifests = OwnedToolchainComponentManifests::parse(sources).unwrap();
+        let targets = &manifests.get()[&ToolchainComponent::Llvm].cmake_properties.imported_targets;
+
+        let Some(ManifestCMakeImportedTarget::Inherent {
+            inherent_target:
+                ManifestCMakeInherentTarget::StaticLibrary {
+                    imported_configurations,
+                    interface_include_directories,
+                    location,
+                    locations,
+                    ..
+                },
+            ..
+        }) = targets.get("LLVMSupport")
+        else {
+            panic!("expected `LLVMSupport` to be a static library");
+        };
+        assert_eq!(location, r"lib\libLLVMSupport.a");
+        assert_eq!(interface_include_directories.get(0), Some(r"include\llvm"));
+        assert_eq!(
+            locations.select("Release", imported_configurations, location),
+            r"lib\release\libLLVMSupport.a"
+        );
+        assert_eq!(
+            locations.select("Debug", VarZeroSlice::new_empty(), location),
+            r"lib\libLLVMSupport.a"
+        );
+
+        let Some(ManifestCMakeImportedTarget::Inherent {
+            name,
+            inherent_target: ManifestCMakeInherentTarget::SharedLibrary { imported_soname, .. },
+            ..
+        }) = targets.get("LLVMDemangle")
+        else {
+            panic!("expected `LLVMDemangle` to be a shared library");
+        };
+        assert_eq!(name, "LLVMDemangle");
+        assert_eq!(imported_soname.as_deref(), Some(r"lib\LLVMDemangle.so"));
+    }
+
+    #[test]
+    fn imported_configurations() {
+        let sources = BTreeMap::from([(ToolchainComponent::Llvm, String::from(MANIFEST))]);
+        let manifests = OwnedToolchainComponentManifests::parse(sources).unwrap();
+        assert_eq!(manifests.imported_configurations(), BTreeSet::from(["RELEASE"]));
+    }
+}
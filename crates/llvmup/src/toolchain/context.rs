@@ -1,6 +1,6 @@
 use crate::{ToolchainPlatform, ToolchainRelease, ToolchainRevision, ToolchainVariant};
 
-#[derive(Clone, Copy, Debug, Hash)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct ToolchainContext {
     pub variant: ToolchainVariant,
     pub release: ToolchainRelease,
@@ -8,6 +8,39 @@ pub struct ToolchainContext {
     pub platform: ToolchainPlatform,
 }
 
+/// The context as its release assets are named (e.g., `llvmorg-17.0.6-x86_64-linux-gnu+rev2`).
+impl core::fmt::Display for ToolchainContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let Self {
+            variant,
+            release,
+            revision,
+            platform,
+        } = self;
+        write!(f, "{variant}-{release}-{platform}{revision}")
+    }
+}
+
+impl core::str::FromStr for ToolchainContext {
+    type Err = crate::toolchain::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || crate::toolchain::Error::ToolchainContextParse {
+            input: input.to_owned(),
+        };
+        // NOTE: Neither the variant nor the release contain a `-`, so the platform is whatever follows them.
+        let (rest, revision) = input.split_at(input.find('+').unwrap_or(input.len()));
+        let (variant, rest) = rest.split_once('-').ok_or_else(error)?;
+        let (release, platform) = rest.split_once('-').ok_or_else(error)?;
+        Ok(Self {
+            variant: variant.parse()?,
+            release: release.parse()?,
+            revision: revision.parse()?,
+            platform: platform.parse()?,
+        })
+    }
+}
+
 impl ToolchainContext {
     #[must_use]
     #[cfg_attr(feature = "tracing", tracing::instrument)]
@@ -25,3 +58,44 @@ impl ToolchainContext {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for input in [
+            "llvmorg-17.0.6-x86_64-linux-gnu",
+            "llvmorg-17.0.6-x86_64-linux-gnu+rev2",
+            "swift-5.10-arm64-macos",
+        ] {
+            assert_eq!(input.parse::<ToolchainContext>().unwrap().to_string(), input);
+        }
+        let context = "llvmorg-17.0.6-x86_64-windows-msvc+rev1".parse::<ToolchainContext>().unwrap();
+        assert_eq!(
+            context,
+            ToolchainContext::new(
+                ToolchainVariant::Llvmorg,
+                ToolchainRelease::new(17, 0, Some(6)),
+                ToolchainRevision::new(Some(1)),
+                ToolchainPlatform::X86_64_WINDOWS_MSVC,
+            )
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for input in [
+            "",
+            "llvmorg",
+            "llvmorg-17.0.6",
+            "llvmorg-17.0.6-x86_64-linux-gnu+2",
+            "llvmorg-17-x86_64-linux-gnu",
+            "gcc-17.0.6-x86_64-linux-gnu",
+            "llvmorg-17.0.6-x86_64-plan9",
+        ] {
+            assert!(input.parse::<ToolchainContext>().is_err(), "{input}");
+        }
+    }
+}
@@ -21,6 +21,20 @@ pub enum ToolchainPlatform {
 }
 
 impl ToolchainPlatform {
+    pub const ALL: [Self; 11] = [
+        ToolchainPlatform::AARCH64_LINUX_GNU,
+        ToolchainPlatform::AARCH64_WINDOWS_MSVC,
+        ToolchainPlatform::ARM64_MACOS,
+        ToolchainPlatform::ARMV7_LINUX_GNUEABIHF,
+        ToolchainPlatform::I686_LINUX_GNU,
+        ToolchainPlatform::POWERPC64LE_LINUX_GNU,
+        ToolchainPlatform::RISCV64_LINUX_GNU,
+        ToolchainPlatform::S390X_LINUX_GNU,
+        ToolchainPlatform::X86_64_MACOS,
+        ToolchainPlatform::X86_64_LINUX_GNU,
+        ToolchainPlatform::X86_64_WINDOWS_MSVC,
+    ];
+
     #[must_use]
     #[cfg_attr(feature = "tracing", tracing::instrument)]
     pub fn detect() -> Self {
@@ -28,7 +42,7 @@ impl ToolchainPlatform {
         return ToolchainPlatform::AARCH64_LINUX_GNU;
         #[cfg(all(target_os = "linux", target_arch = "arm"))]
         return ToolchainPlatform::ARMV7_LINUX_GNUEABIHF;
-        #[cfg(all(target_os = "linux", target_arch = "powerpc64le"))]
+        #[cfg(all(target_os = "linux", target_arch = "powerpc64", target_endian = "little"))]
         return ToolchainPlatform::POWERPC64LE_LINUX_GNU;
         #[cfg(all(target_os = "linux", target_arch = "riscv64"))]
         return ToolchainPlatform::RISCV64_LINUX_GNU;
@@ -68,6 +82,27 @@ impl ToolchainPlatform {
         }
     }
 
+    /// The platform as an LLVM target triple (e.g., for `--target`).
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn llvm_target_triple(&self) -> &'static str {
+        #![allow(clippy::enum_glob_use)]
+        use ToolchainPlatform::*;
+        match self {
+            AARCH64_LINUX_GNU => "aarch64-unknown-linux-gnu",
+            AARCH64_WINDOWS_MSVC => "aarch64-pc-windows-msvc",
+            ARM64_MACOS => "arm64-apple-darwin",
+            ARMV7_LINUX_GNUEABIHF => "armv7-unknown-linux-gnueabihf",
+            I686_LINUX_GNU => "i686-unknown-linux-gnu",
+            POWERPC64LE_LINUX_GNU => "powerpc64le-unknown-linux-gnu",
+            RISCV64_LINUX_GNU => "riscv64-unknown-linux-gnu",
+            S390X_LINUX_GNU => "s390x-unknown-linux-gnu",
+            X86_64_MACOS => "x86_64-apple-darwin",
+            X86_64_LINUX_GNU => "x86_64-unknown-linux-gnu",
+            X86_64_WINDOWS_MSVC => "x86_64-pc-windows-msvc",
+        }
+    }
+
     #[must_use]
     #[cfg_attr(feature = "tracing", tracing::instrument)]
     pub fn sys(&self) -> ToolchainSys {
@@ -106,3 +141,16 @@ impl core::fmt::Display for ToolchainPlatform {
         write!(f, "{platform}")
     }
 }
+
+impl core::str::FromStr for ToolchainPlatform {
+    type Err = crate::toolchain::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|platform| platform.to_string() == input)
+            .ok_or_else(|| crate::toolchain::Error::ToolchainPlatformParse {
+                input: input.to_owned(),
+            })
+    }
+}
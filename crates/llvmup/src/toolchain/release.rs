@@ -22,3 +22,89 @@ impl ToolchainRelease {
         Self { major, minor, patch }
     }
 }
+
+impl core::str::FromStr for ToolchainRelease {
+    type Err = crate::toolchain::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let error = || crate::toolchain::Error::ToolchainReleaseParse {
+            input: input.to_owned(),
+        };
+        let mut parts = input.split('.').map(str::parse::<usize>);
+        let major = parts.next().and_then(Result::ok).ok_or_else(error)?;
+        let minor = parts.next().and_then(Result::ok).ok_or_else(error)?;
+        let patch = parts.next().map(|patch| patch.ok().ok_or_else(error)).transpose()?;
+        if parts.next().is_some() {
+            return Err(error());
+        }
+        Ok(Self { major, minor, patch })
+    }
+}
+
+impl ToolchainRelease {
+    #[must_use]
+    pub fn major(&self) -> usize {
+        self.major
+    }
+
+    #[must_use]
+    pub fn minor(&self) -> usize {
+        self.minor
+    }
+
+    #[must_use]
+    pub fn patch(&self) -> Option<usize> {
+        self.patch
+    }
+}
+
+#[cfg(feature = "config")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ToolchainReleaseConstraint {
+    Exact(ToolchainRelease),
+    Range(semver::VersionReq),
+}
+
+#[cfg(feature = "config")]
+impl ToolchainReleaseConstraint {
+    #[must_use]
+    #[cfg_attr(feature = "tracing", tracing::instrument)]
+    pub fn matches(&self, release: &ToolchainRelease) -> bool {
+        match self {
+            Self::Exact(exact) => exact == release,
+            Self::Range(range) => {
+                let major = release.major as u64;
+                let minor = release.minor as u64;
+                let patch = release.patch.unwrap_or_default() as u64;
+                range.matches(&semver::Version::new(major, minor, patch))
+            },
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl core::fmt::Display for ToolchainReleaseConstraint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Exact(release) => write!(f, "{release}"),
+            Self::Range(range) => write!(f, "{range}"),
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl core::str::FromStr for ToolchainReleaseConstraint {
+    type Err = crate::toolchain::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        // NOTE: A bare release (e.g., `17.0.6`) is pinned exactly rather than treated as a caret requirement.
+        if let Ok(release) = input.parse() {
+            return Ok(Self::Exact(release));
+        }
+        semver::VersionReq::parse(input)
+            .map(Self::Range)
+            .map_err(|_| crate::toolchain::Error::ToolchainReleaseParse {
+                input: input.to_owned(),
+            })
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Clone, Copy, Debug, Hash)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct ToolchainRevision(Option<usize>);
 
 impl core::fmt::Display for ToolchainRevision {
@@ -17,3 +17,27 @@ impl ToolchainRevision {
         Self(revision)
     }
 }
+
+impl core::str::FromStr for ToolchainRevision {
+    type Err = crate::toolchain::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.is_empty() {
+            return Ok(Self(None));
+        }
+        input
+            .strip_prefix("+rev")
+            .and_then(|revision| revision.parse().ok())
+            .map(|revision| Self(Some(revision)))
+            .ok_or_else(|| crate::toolchain::Error::ToolchainRevisionParse {
+                input: input.to_owned(),
+            })
+    }
+}
+
+impl ToolchainRevision {
+    #[must_use]
+    pub fn get(&self) -> Option<usize> {
+        self.0
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Clone, Copy, Debug, Hash)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum ToolchainVariant {
     Llvmorg,
     Swift,
@@ -12,3 +12,17 @@ impl core::fmt::Display for ToolchainVariant {
         }
     }
 }
+
+impl core::str::FromStr for ToolchainVariant {
+    type Err = crate::toolchain::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "llvmorg" => Ok(Self::Llvmorg),
+            "swift" => Ok(Self::Swift),
+            _ => Err(crate::toolchain::Error::ToolchainVariantParse {
+                input: input.to_owned(),
+            }),
+        }
+    }
+}